@@ -0,0 +1,306 @@
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::task;
+
+use crate::{ListOptions, Resources, Result};
+
+/// An async version of [`Resources`] for use on a tokio runtime.  Every method mirrors the
+/// method of the same name on [`Resources`], with the same path semantics and errors, and
+/// runs the blocking file access on tokio's blocking thread pool.  The methods of the
+/// format extension traits are available as inherent methods when their features are
+/// enabled.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::{AsyncResources, Resources};
+/// // Note you need to enable the tokio_resources feature in Cargo.toml
+///
+/// # async fn run() {
+/// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+/// let resources = AsyncResources::new(resources);
+///
+/// // Read the motd.txt file in the projectile directory without blocking the runtime.
+/// let motd = resources.load_from_file("motd.txt").await.unwrap();
+/// # }
+/// ```
+///
+/// Saving and listing files in a temporary directory:
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use appres::{AsyncResources, Resources};
+///
+/// let dir = std::env::temp_dir().join(format!("appres-async-{}", std::process::id()));
+/// let resources = AsyncResources::new(Resources::new(&dir));
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async {
+///     resources.save_to_file("notes/motd.txt", "Hello World").await.unwrap();
+///     let motd = resources.load_from_file("notes/motd.txt").await.unwrap();
+///     assert_eq!(motd, "Hello World");
+///
+///     let notes = resources.list_dir("notes").await.unwrap();
+///     assert_eq!(notes, vec![PathBuf::from("notes/motd.txt")]);
+/// });
+/// std::fs::remove_dir_all(dir).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct AsyncResources {
+    resources: Resources,
+}
+
+impl From<Resources> for AsyncResources {
+    fn from(resources: Resources) -> Self {
+        Self::new(resources)
+    }
+}
+
+impl AsyncResources {
+    /// Creates an async resource manager from a resource manager.
+    pub fn new(resources: Resources) -> Self {
+        Self { resources }
+    }
+
+    /// Returns the blocking resource manager.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Returns the blocking resource manager.
+    pub fn into_resources(self) -> Resources {
+        self.resources
+    }
+
+    /// Runs `f` with the resources on the blocking thread pool.
+    pub(crate) async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Resources) -> Result<T> + Send + 'static,
+    {
+        let resources = self.resources.clone();
+        match task::spawn_blocking(move || f(&resources)).await {
+            Ok(result) => result,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(Error::new(ErrorKind::Interrupted, err).into()),
+        }
+    }
+
+    /// Async version of [`Resources::load_from_file`].
+    pub async fn load_from_file(&self, path: impl AsRef<Path>) -> Result<String> {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_file(path))
+            .await
+    }
+
+    /// Async version of [`Resources::load_bytes`].
+    pub async fn load_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_bytes(path)).await
+    }
+
+    /// Async version of [`Resources::save_to_file`].
+    pub async fn save_to_file(
+        &self,
+        path: impl AsRef<Path>,
+        content: impl Into<Vec<u8>>,
+    ) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        let content = content.into();
+        self.run(move |resources| resources.save_to_file(path, content))
+            .await
+    }
+
+    /// Async version of [`Resources::remove_file`].
+    pub async fn remove_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.remove_file(path)).await
+    }
+
+    /// Async version of [`Resources::has_file`].
+    pub async fn has_file(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| Ok(resources.has_file(path)))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Async version of [`Resources::has_dir`].
+    pub async fn has_dir(&self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| Ok(resources.has_dir(path)))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Async version of [`Resources::list_dir`].
+    pub async fn list_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        self.list_dir_with(path, ListOptions::default()).await
+    }
+
+    /// Async version of [`Resources::list_dir_with`].
+    pub async fn list_dir_with(
+        &self,
+        path: impl AsRef<Path>,
+        options: ListOptions,
+    ) -> Result<Vec<PathBuf>> {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.list_dir_with(path, options))
+            .await
+    }
+
+    /// Async version of [`Resources::walk`].
+    pub async fn walk(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        self.walk_with(path, ListOptions::default()).await
+    }
+
+    /// Async version of [`Resources::walk_with`].
+    pub async fn walk_with(
+        &self,
+        path: impl AsRef<Path>,
+        options: ListOptions,
+    ) -> Result<Vec<PathBuf>> {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.walk_with(path, options))
+            .await
+    }
+
+    /// Async version of [`Resources::glob`].
+    pub async fn glob(&self, pattern: impl AsRef<str>) -> Result<Vec<PathBuf>> {
+        self.glob_with(pattern, ListOptions::default()).await
+    }
+
+    /// Async version of [`Resources::glob_with`].
+    pub async fn glob_with(
+        &self,
+        pattern: impl AsRef<str>,
+        options: ListOptions,
+    ) -> Result<Vec<PathBuf>> {
+        let pattern = pattern.as_ref().to_string();
+        self.run(move |resources| resources.glob_with(pattern, options))
+            .await
+    }
+
+    /// Async version of [`Resources::load_mmap`].
+    ///
+    /// # Safety
+    ///
+    /// The same as for [`Resources::load_mmap`]: the file must not be truncated or modified
+    /// while the returned [`MappedFile`](crate::MappedFile) is alive.
+    #[cfg(feature = "mmap_resources")]
+    pub async unsafe fn load_mmap(&self, path: impl AsRef<Path>) -> Result<crate::MappedFile> {
+        let path = path.as_ref().to_path_buf();
+        // SAFETY: the caller upholds the contract of load_mmap.
+        self.run(move |resources| unsafe { resources.load_mmap(path) })
+            .await
+    }
+}
+
+#[cfg(feature = "serde_resources")]
+impl AsyncResources {
+    /// Async version of [`Resources::load`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::{AsyncResources, Resources};
+    /// // Note you need to enable the tokio_resources feature in Cargo.toml
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// # async fn run() {
+    /// let resources = AsyncResources::new(Resources::new_dir_relative_to_executable("assets").unwrap());
+    ///
+    /// // Load and parse the config file in the assets folder, whatever its format
+    /// let config: Config = resources.load("config.toml").await.unwrap();
+    /// # }
+    /// ```
+    pub async fn load<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.load(path)).await
+    }
+
+    /// Async version of [`Resources::load_validated`].
+    pub async fn load_validated<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + crate::Validate + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_validated(path))
+            .await
+    }
+
+    /// Async version of [`Resources::save`].  The object is serialized before the file is
+    /// written on the blocking thread pool.
+    pub async fn save<C>(&self, path: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let path = path.as_ref().to_path_buf();
+        let content = self.resources.serialize_for(&path, thing)?;
+        self.save_to_file(path, content).await
+    }
+
+    /// Async version of [`Resources::load_or_init`].
+    pub async fn load_or_init<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_or_init(path))
+            .await
+    }
+
+    /// Async version of [`Resources::load_or_init_with`].
+    pub async fn load_or_init_with<T>(
+        &self,
+        path: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_or_init_with(path, default, fill_missing))
+            .await
+    }
+
+    /// Async version of [`Resources::with_locked`].  The lock is held on the blocking
+    /// thread pool while `modify` runs.
+    pub async fn with_locked<T, R, F>(&self, path: impl AsRef<Path>, modify: F) -> Result<R>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize + Default,
+        R: Send + 'static,
+        F: FnOnce(&mut T) -> R + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.with_locked(path, modify))
+            .await
+    }
+
+    /// Async version of [`Resources::update`].
+    pub async fn update<T, F>(&self, path: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T) + Send + 'static,
+    {
+        let path = path.as_ref().to_path_buf();
+        self.run(move |resources| resources.update(path, modify))
+            .await
+    }
+}
@@ -0,0 +1,195 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::{create_dir_all, remove_file, rename, File, OpenOptions};
+use std::io::{Error, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{AppResError, Result};
+
+/// How many symlinks are followed at the destination before giving up, as on Linux.
+const MAX_LINKS: usize = 40;
+
+/// Counter used to keep temporary file names unique within the process.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Controls how files are written to disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Writes go to a temporary file in the same directory which is synced and then renamed
+    /// over the destination, so readers (and the file after a crash) only ever see the old
+    /// or the new content.  This is the default.
+    #[default]
+    Atomic,
+    /// Writes go straight to the destination file.  Faster, but a crash mid-write can leave
+    /// a truncated file behind.
+    Direct,
+}
+
+/// Writes `content` to `path` using the given [`WriteMode`], creating any missing parent
+/// directories first.
+pub(crate) fn write_file(path: &Path, content: &[u8], mode: WriteMode) -> Result<()> {
+    let mut file = PendingFile::create(path, mode)?;
+    file.write_all(content)?;
+    file.commit()
+}
+
+/// A file being written with a [`WriteMode`].  With [`WriteMode::Atomic`], the content goes
+/// to a temporary file that only replaces the destination once [`PendingFile::commit`] is
+/// called, and is thrown away if the pending file is dropped instead.
+pub(crate) struct PendingFile {
+    file: File,
+    path: PathBuf,
+    temp_path: Option<PathBuf>,
+}
+
+impl PendingFile {
+    /// Opens the file for writing, creating any missing parent directories first.
+    pub(crate) fn create(path: &Path, mode: WriteMode) -> Result<Self> {
+        let not_a_file_path = || AppResError::NotAFilePath(path.to_path_buf());
+        if path.file_name().is_none() {
+            return Err(not_a_file_path());
+        }
+        // Renaming over a symlink would replace the link, so write to its target instead.
+        let path = &match mode {
+            WriteMode::Atomic => resolve_links(path)?,
+            WriteMode::Direct => path.to_path_buf(),
+        };
+        let parent = path.parent().ok_or_else(not_a_file_path)?;
+        create_dir_all(parent)?;
+
+        if mode == WriteMode::Direct {
+            return Ok(Self {
+                file: File::create(path)?,
+                path: path.to_path_buf(),
+                temp_path: None,
+            });
+        }
+
+        let temp_path = temp_path_for(path, parent).ok_or_else(not_a_file_path)?;
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        let pending_file = Self {
+            file,
+            path: path.to_path_buf(),
+            temp_path: Some(temp_path),
+        };
+
+        // Keep the permissions of the file being replaced, if there is one.
+        if let Ok(metadata) = path.metadata() {
+            pending_file.file.set_permissions(metadata.permissions())?;
+        }
+        Ok(pending_file)
+    }
+
+    /// Puts the written content in place of the destination.
+    pub(crate) fn commit(mut self) -> Result<()> {
+        let temp_path = match self.temp_path.take() {
+            Some(temp_path) => temp_path,
+            None => return Ok(self.file.flush()?),
+        };
+
+        let result = self
+            .file
+            .sync_all()
+            .and_then(|_| rename(&temp_path, &self.path));
+        if let Err(err) = result {
+            let _ = remove_file(&temp_path);
+            return Err(err.into());
+        }
+
+        sync_dir(self.path.parent().ok_or(AppResError::NoParent)?)
+    }
+}
+
+impl Write for PendingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        if let Some(temp_path) = self.temp_path.take() {
+            let _ = remove_file(temp_path);
+        }
+    }
+}
+
+/// Follows the symlinks at the path, returning the file they end up at, which does not
+/// have to exist.
+fn resolve_links(path: &Path) -> Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_LINKS {
+        match path.symlink_metadata() {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = path.read_link()?;
+                // Relative targets are relative to the directory of the link.
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    Err(Error::other("too many levels of symbolic links").into())
+}
+
+fn temp_path_for(path: &Path, parent: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?;
+
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    Some(parent.join(temp_name))
+}
+
+/// Returns whether the file name is that of a temporary file of an atomic write,
+/// `.name.<pid>.<counter>.tmp`.
+pub(crate) fn is_temp_file_name(name: &OsStr) -> bool {
+    let name = match name
+        .to_str()
+        .and_then(|name| name.strip_prefix('.'))
+        .and_then(|name| name.strip_suffix(".tmp"))
+    {
+        Some(name) => name,
+        None => return false,
+    };
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+
+    let mut parts = name.rsplitn(3, '.');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(counter), Some(pid), Some(file_name)) => {
+            is_number(counter) && is_number(pid) && !file_name.is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Flushes the directory entry so that the rename itself survives a crash.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    Ok(std::fs::File::open(dir)?.sync_all()?)
+}
+
+/// Directories cannot be opened for syncing on this platform, the rename is the best we can do.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,360 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{metadata, read, read_dir, remove_file, symlink_metadata};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::sandbox::normalize;
+use crate::{atomic, EntryKind, Result, WriteMode};
+
+/// Storage for resources.  Paths are relative to the root of the backend, which is the
+/// directory the [`Resources`](crate::Resources) were created for.
+///
+/// Resources are stored on the file system by default, see [`FsBackend`].  Use
+/// [`Resources::with_backend`](crate::Resources::with_backend) to store them somewhere else,
+/// e.g. in memory with [`MemoryBackend`] so that tests do not touch the real config
+/// directory.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use appres::MemoryBackend;
+/// use appres::Resources;
+///
+/// let backend = MemoryBackend::new().with_file("greeting.txt", "Hello");
+/// let resources = Resources::new("/home/nobody/.config/projectile").with_backend(backend);
+///
+/// assert_eq!(resources.load_from_file("greeting.txt").unwrap(), "Hello");
+/// resources.save_to_file("greeting.txt", "Bye").unwrap();
+/// assert_eq!(resources.load_from_file("greeting.txt").unwrap(), "Bye");
+/// ```
+pub trait Backend: fmt::Debug + Send + Sync {
+    /// Reads the content of a file.
+    fn read(&self, path: &Path) -> Result<Vec<u8>>;
+
+    /// Writes the content of a file, creating it and its parent directories if needed.
+    /// Backends that cannot write atomically ignore the [`WriteMode`].
+    fn write(&self, path: &Path, content: &[u8], write_mode: WriteMode) -> Result<()>;
+
+    /// Checks to see if there is a file or a directory at the path.
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    /// Lists the entries of a directory.
+    fn list(&self, path: &Path) -> Result<Vec<DirEntry>>;
+
+    /// Removes a file.
+    fn remove(&self, path: &Path) -> Result<()>;
+
+    /// Returns the metadata of a file or a directory.
+    fn metadata(&self, path: &Path) -> Result<Metadata>;
+
+    /// Returns where the path is stored on the local file system, if it is stored there.
+    /// Strict paths resolve the symlinks along it.  Defaults to `None`.
+    fn local_path(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Metadata of a file or a directory in a [`Backend`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    /// Whether this is a file or a directory.
+    pub kind: EntryKind,
+    /// Length of the file in bytes.
+    pub len: u64,
+    /// When the file was last modified, if known.
+    pub modified: Option<SystemTime>,
+}
+
+/// An entry of a directory listed by [`Backend::list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Name of the entry within the directory.
+    pub name: OsString,
+    /// Whether this is a file or a directory, following symlinks.
+    pub kind: EntryKind,
+    /// Whether the entry is a symlink.  Symlinked directories are not walked into.
+    pub is_symlink: bool,
+}
+
+/// Returns the error for a path that a backend does not have.
+pub(crate) fn not_found() -> Error {
+    Error::from(ErrorKind::NotFound)
+}
+
+/// Stores resources in a directory of the file system.  This is the backend used by every
+/// constructor of [`Resources`](crate::Resources).
+#[derive(Clone, Debug)]
+pub struct FsBackend {
+    root: PathBuf,
+}
+
+impl FsBackend {
+    /// Creates a backend for the directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Backend for FsBackend {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        Ok(read(self.root.join(path))?)
+    }
+
+    fn write(&self, path: &Path, content: &[u8], write_mode: WriteMode) -> Result<()> {
+        atomic::write_file(&self.root.join(path), content, write_mode)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.root.join(path).exists()
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<DirEntry>> {
+        let mut entries = Vec::new();
+        for entry in read_dir(self.root.join(path))? {
+            let entry = entry?;
+            let is_symlink = symlink_metadata(entry.path())?.file_type().is_symlink();
+            // Dangling symlinks are listed as files.
+            let is_dir = metadata(entry.path()).is_ok_and(|metadata| metadata.is_dir());
+            entries.push(DirEntry {
+                name: entry.file_name(),
+                kind: if is_dir {
+                    EntryKind::Dir
+                } else {
+                    EntryKind::File
+                },
+                is_symlink,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        Ok(remove_file(self.root.join(path))?)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        let metadata = metadata(self.root.join(path))?;
+        Ok(Metadata {
+            kind: if metadata.is_dir() {
+                EntryKind::Dir
+            } else {
+                EntryKind::File
+            },
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(self.root.join(path))
+    }
+}
+
+/// Content and modification time of the files in a [`MemoryBackend`].
+type Files = BTreeMap<PathBuf, (Vec<u8>, SystemTime)>;
+
+/// Stores resources in memory.  Directories exist as long as they have files in them.
+/// Clones share the same files.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryBackend {
+    files: Arc<Mutex<Files>>,
+}
+
+impl MemoryBackend {
+    /// Creates a backend without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the backend.
+    pub fn with_file(self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Self {
+        if let Some(path) = normalize(path.as_ref()) {
+            self.files()
+                .insert(path, (content.as_ref().to_vec(), SystemTime::now()));
+        }
+        self
+    }
+
+    fn files(&self) -> MutexGuard<'_, Files> {
+        // A panic while holding the lock cannot leave the map half updated.
+        self.files.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        normalize(path)
+            .and_then(|key| self.files().get(&key).map(|(content, _)| content.clone()))
+            .ok_or_else(|| not_found().into())
+    }
+
+    fn write(&self, path: &Path, content: &[u8], _write_mode: WriteMode) -> Result<()> {
+        let key = normalize(path).ok_or_else(not_found)?;
+        self.files()
+            .insert(key, (content.to_vec(), SystemTime::now()));
+        Ok(())
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<DirEntry>> {
+        let dir = normalize(path).ok_or_else(not_found)?;
+
+        let mut entries: Vec<DirEntry> = Vec::new();
+        for file in self.files().keys() {
+            let mut rest = match file.strip_prefix(&dir) {
+                Ok(rest) => rest.components(),
+                Err(_) => continue,
+            };
+            let name = match rest.next() {
+                Some(name) => name.as_os_str().to_os_string(),
+                None => continue,
+            };
+            let kind = match rest.next() {
+                Some(_) => EntryKind::Dir,
+                None => EntryKind::File,
+            };
+            if !entries.iter().any(|entry| entry.name == name) {
+                entries.push(DirEntry {
+                    name,
+                    kind,
+                    is_symlink: false,
+                });
+            }
+        }
+
+        if entries.is_empty() && !dir.as_os_str().is_empty() {
+            return Err(not_found().into());
+        }
+        Ok(entries)
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        normalize(path)
+            .and_then(|key| self.files().remove(&key))
+            .map(|_| ())
+            .ok_or_else(|| not_found().into())
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        let key = normalize(path).ok_or_else(not_found)?;
+        let files = self.files();
+        if let Some((content, modified)) = files.get(&key) {
+            return Ok(Metadata {
+                kind: EntryKind::File,
+                len: content.len() as u64,
+                modified: Some(*modified),
+            });
+        }
+
+        let is_dir = key.as_os_str().is_empty()
+            || files
+                .keys()
+                .any(|file| file.starts_with(&key) && *file != key);
+        if !is_dir {
+            return Err(not_found().into());
+        }
+        Ok(Metadata {
+            kind: EntryKind::Dir,
+            len: 0,
+            modified: None,
+        })
+    }
+}
+
+/// Reads from one backend, falling back to another one for the files that the first one
+/// does not have.  Everything is written to the first backend.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use appres::{MemoryBackend, OverlayBackend};
+/// use appres::Resources;
+///
+/// let defaults = MemoryBackend::new().with_file("theme.toml", "bg = 0");
+/// let user = MemoryBackend::new();
+/// let resources = Resources::new("/home/nobody/.config/projectile")
+///     .with_backend(OverlayBackend::new(user, defaults));
+///
+/// assert_eq!(resources.load_from_file("theme.toml").unwrap(), "bg = 0");
+/// resources.save_to_file("theme.toml", "bg = 1").unwrap();
+/// assert_eq!(resources.load_from_file("theme.toml").unwrap(), "bg = 1");
+/// ```
+#[derive(Clone, Debug)]
+pub struct OverlayBackend {
+    upper: Arc<dyn Backend>,
+    lower: Arc<dyn Backend>,
+}
+
+impl OverlayBackend {
+    /// Creates a backend that reads from `upper`, then from `lower`.
+    pub fn new(upper: impl Backend + 'static, lower: impl Backend + 'static) -> Self {
+        Self::from_arcs(Arc::new(upper), Arc::new(lower))
+    }
+
+    pub(crate) fn from_arcs(upper: Arc<dyn Backend>, lower: Arc<dyn Backend>) -> Self {
+        Self { upper, lower }
+    }
+
+    /// Returns the backend that has the path.
+    fn backend_for(&self, path: &Path) -> &dyn Backend {
+        if !self.upper.exists(path) && self.lower.exists(path) {
+            self.lower.as_ref()
+        } else {
+            self.upper.as_ref()
+        }
+    }
+}
+
+impl Backend for OverlayBackend {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.backend_for(path).read(path)
+    }
+
+    fn write(&self, path: &Path, content: &[u8], write_mode: WriteMode) -> Result<()> {
+        self.upper.write(path, content, write_mode)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.upper.exists(path) || self.lower.exists(path)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<DirEntry>> {
+        let lower = match self.lower.exists(path) {
+            true => self.lower.list(path)?,
+            false => return self.upper.list(path),
+        };
+        // The directory does not have to exist in both backends.
+        let mut entries = match self.upper.exists(path) {
+            true => self.upper.list(path)?,
+            false => Vec::new(),
+        };
+
+        for entry in lower {
+            if !entries.iter().any(|existing| existing.name == entry.name) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        self.upper.remove(path)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        self.backend_for(path).metadata(path)
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        self.upper.local_path(path)
+    }
+}
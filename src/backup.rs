@@ -0,0 +1,297 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::ResultExt;
+use crate::{AppResError, EntryKind, Operation, Resources, Result};
+
+/// Name of the directory that [`BackupPolicy::Timestamped`] keeps backups in.
+const BACKUP_DIR: &str = ".backups";
+
+/// Controls whether the previous version of a file is kept when it is overwritten by
+/// [`Resources::save_to_file`] or any of the methods that save through it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackupPolicy {
+    /// Files are overwritten without a backup.  This is the default.
+    #[default]
+    Disabled,
+    /// Keeps up to `keep` previous versions next to the file, `settings.yaml.1` being the
+    /// most recent one.  Older versions are renumbered on every save.
+    Numbered {
+        /// How many previous versions to keep.
+        keep: usize,
+    },
+    /// Keeps up to `keep` previous versions in a `.backups` directory next to the file,
+    /// named after the time they were replaced in UTC, e.g.
+    /// `.backups/settings.yaml.20240131T235959.999Z`.
+    Timestamped {
+        /// How many previous versions to keep.
+        keep: usize,
+    },
+}
+
+impl Resources {
+    /// Sets whether the previous version of a file is kept when it is saved.  A backup is
+    /// only made when the content of the file changes.  Streams opened with
+    /// [`Resources::open_writer`] are not backed up.  Defaults to
+    /// [`BackupPolicy::Disabled`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{BackupPolicy, Resources};
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile")
+    ///     .unwrap()
+    ///     .with_backups(BackupPolicy::Numbered { keep: 3 });
+    ///
+    /// // The previous settings are kept in settings.yaml.1.
+    /// resources.save_to_file("settings.yaml", "theme: dark\n").unwrap();
+    /// ```
+    pub fn with_backups(mut self, backups: BackupPolicy) -> Self {
+        self.backups = backups;
+        self
+    }
+
+    /// Returns whether the previous version of a file is kept when it is saved.
+    pub fn backups(&self) -> BackupPolicy {
+        self.backups
+    }
+
+    /// Lists the backups of a file at the path specified relative to the directory that was
+    /// given when the resource manager was created, most recent first.  The paths are
+    /// relative to the directory as well.  Backups are looked for where the
+    /// [`BackupPolicy`] set with [`Resources::with_backups`] keeps them, so none are found
+    /// while backups are disabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{BackupPolicy, Resources};
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile")
+    ///     .unwrap()
+    ///     .with_backups(BackupPolicy::Timestamped { keep: 10 });
+    ///
+    /// for backup in resources.list_backups("settings.yaml").unwrap() {
+    ///     println!("{}", backup.display());
+    /// }
+    /// ```
+    pub fn list_backups(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let path = self.backend_path(path.as_ref())?;
+        let (dir, name) = match backup_location(self.backups, &path) {
+            Some(location) => location,
+            None => return Ok(Vec::new()),
+        };
+
+        let entries = match self.backend.list(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.is_not_found() => return Ok(Vec::new()),
+            Err(err) => return Err(err).context(&self.get_file_path(&dir), Operation::Read),
+        };
+
+        let mut prefix = name;
+        prefix.push(".");
+        let prefix = prefix.to_string_lossy().into_owned();
+        let mut backups: Vec<(String, PathBuf)> = entries
+            .into_iter()
+            .filter(|entry| entry.kind == EntryKind::File)
+            .filter_map(|entry| {
+                let entry_name = entry.name.to_str()?;
+                let suffix = entry_name.strip_prefix(&prefix)?;
+                let is_backup = match self.backups {
+                    BackupPolicy::Numbered { .. } => is_number(suffix),
+                    _ => is_timestamp(suffix),
+                };
+                is_backup.then(|| (suffix.to_string(), dir.join(entry_name)))
+            })
+            .collect();
+
+        match self.backups {
+            BackupPolicy::Numbered { .. } => {
+                backups.sort_by_key(|(suffix, _)| suffix.parse::<usize>().unwrap_or_default())
+            }
+            // Timestamps sort the same as the times they stand for.
+            _ => backups.sort_by(|(a, _), (b, _)| b.cmp(a)),
+        }
+        Ok(backups.into_iter().map(|(_, backup)| backup).collect())
+    }
+
+    /// Restores a backup of a file at the path specified relative to the directory that was
+    /// given when the resource manager was created, `1` being the most recent backup as
+    /// returned by [`Resources::list_backups`].  The restored file is saved like any other,
+    /// so the version it replaces is backed up in turn.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{BackupPolicy, Resources};
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile")
+    ///     .unwrap()
+    ///     .with_backups(BackupPolicy::Numbered { keep: 3 });
+    ///
+    /// // Go back to the settings from before the last save.
+    /// resources.restore_backup("settings.yaml", 1).unwrap();
+    /// ```
+    pub fn restore_backup(&self, path: impl AsRef<Path>, n: usize) -> Result<()> {
+        let path = path.as_ref();
+        let backups = self.list_backups(path)?;
+        let backup = n
+            .checked_sub(1)
+            .and_then(|index| backups.get(index))
+            .ok_or_else(|| AppResError::NoBackup {
+                path: self.get_file_path(path),
+                n,
+            })?;
+
+        let content = self
+            .backend
+            .read(backup)
+            .context(&self.get_file_path(backup), Operation::Read)?;
+        self.save_to_file(path, content)
+    }
+
+    /// Keeps the current version of the file at the backend path before `content` replaces
+    /// it, as the backup policy says.
+    pub(crate) fn back_up(&self, path: &Path, content: &[u8]) -> Result<()> {
+        let keep = match self.backups {
+            BackupPolicy::Disabled => return Ok(()),
+            BackupPolicy::Numbered { keep } | BackupPolicy::Timestamped { keep } => keep,
+        };
+        let (dir, name) = match backup_location(self.backups, path) {
+            Some(location) if keep > 0 => location,
+            _ => return Ok(()),
+        };
+
+        let current = match self.backend.read(path) {
+            Ok(current) if current != content => current,
+            Ok(_) => return Ok(()),
+            Err(err) if err.is_not_found() => return Ok(()),
+            Err(err) => return Err(err).context(&self.get_file_path(path), Operation::Read),
+        };
+
+        let backup = match self.backups {
+            BackupPolicy::Numbered { .. } => {
+                // Shift the existing backups up by one, making room for the new one.
+                for number in (1..keep).rev() {
+                    let older = numbered(&dir, &name, number);
+                    if let Ok(older_content) = self.backend.read(&older) {
+                        self.write_backup(&numbered(&dir, &name, number + 1), &older_content)?;
+                    }
+                }
+                numbered(&dir, &name, 1)
+            }
+            _ => {
+                let mut time = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+                // Never overwrite a backup made within the same millisecond.
+                while self.backend.exists(&timestamped(&dir, &name, time)) {
+                    time += 1;
+                }
+                timestamped(&dir, &name, time)
+            }
+        };
+        self.write_backup(&backup, &current)?;
+
+        for old_backup in self.list_backups(path)?.into_iter().skip(keep) {
+            self.backend
+                .remove(&old_backup)
+                .context(&self.get_file_path(&old_backup), Operation::Write)?;
+        }
+        Ok(())
+    }
+
+    fn write_backup(&self, backup: &Path, content: &[u8]) -> Result<()> {
+        self.backend
+            .write(backup, content, self.write_mode)
+            .context(&self.get_file_path(backup), Operation::Write)
+    }
+}
+
+/// Returns the directory that the backups of the file are kept in and the name of the
+/// file, or `None` if backups are disabled or the path has no file name.
+fn backup_location(backups: BackupPolicy, path: &Path) -> Option<(PathBuf, OsString)> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let name = path.file_name()?.to_os_string();
+    match backups {
+        BackupPolicy::Disabled => None,
+        BackupPolicy::Numbered { .. } => Some((dir.to_path_buf(), name)),
+        BackupPolicy::Timestamped { .. } => Some((dir.join(BACKUP_DIR), name)),
+    }
+}
+
+fn numbered(dir: &Path, name: &OsString, number: usize) -> PathBuf {
+    let mut backup = name.clone();
+    backup.push(format!(".{}", number));
+    dir.join(backup)
+}
+
+fn timestamped(dir: &Path, name: &OsString, millis: u128) -> PathBuf {
+    let mut backup = name.clone();
+    backup.push(".");
+    backup.push(timestamp(millis));
+    dir.join(backup)
+}
+
+/// Formats milliseconds since the Unix epoch as `20240131T235959.999Z`.
+fn timestamp(millis: u128) -> String {
+    let seconds = (millis / 1000) as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        millis % 1000
+    )
+}
+
+fn is_number(suffix: &str) -> bool {
+    !suffix.is_empty() && suffix.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Checks whether a string was formatted by [`timestamp`].
+fn is_timestamp(suffix: &str) -> bool {
+    let bytes = suffix.as_bytes();
+    bytes.len() == 20
+        && bytes[8] == b'T'
+        && bytes[15] == b'.'
+        && bytes[19] == b'Z'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(index, byte)| matches!(index, 8 | 15 | 19) || byte.is_ascii_digit())
+}
+
+/// Converts days since the Unix epoch to a year, month and day of the Gregorian calendar.
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
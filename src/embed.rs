@@ -0,0 +1,246 @@
+use std::fmt::Write as _;
+use std::fs::{read_dir, write};
+use std::io::{Error, ErrorKind};
+use std::path::{Component, Path, PathBuf};
+
+use crate::backend::{not_found, Backend, DirEntry, Metadata};
+use crate::{AppResError, EntryKind, Result, WriteMode};
+
+/// A directory of files compiled into the binary.
+///
+/// A build script turns a directory into a table of `include_bytes!` calls with
+/// [`generate`], and the table is included into an [`EmbeddedDir`] that
+/// [`Resources::new_embedded`](crate::Resources::new_embedded) and
+/// [`Resources::with_embedded`](crate::Resources::with_embedded) read from.
+///
+/// # Examples
+///
+/// Basic usage, with appres in both `[dependencies]` and `[build-dependencies]`:
+///
+/// ```no_run
+/// // In build.rs, embed the assets directory next to Cargo.toml.
+/// appres::generate("assets", "assets.rs").unwrap();
+/// ```
+///
+/// ```ignore
+/// // In main.rs, include the generated table.
+/// use appres::EmbeddedDir;
+/// use appres::Resources;
+///
+/// static ASSETS: EmbeddedDir = EmbeddedDir::new(include!(concat!(env!("OUT_DIR"), "/assets.rs")));
+///
+/// // Read the assets from the binary, letting files in the assets folder next to the
+/// // executable override them.
+/// let resources = Resources::new_dir_relative_to_executable("assets")
+///     .unwrap()
+///     .with_embedded(&ASSETS);
+/// let readme = resources.load_from_file("README.md").unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedDir {
+    files: &'static [(&'static str, &'static [u8])],
+}
+
+impl EmbeddedDir {
+    /// Creates an embedded directory from its files, given as paths separated by `/` and
+    /// sorted by path, along with their content.  This is the table written by
+    /// [`generate`].
+    pub const fn new(files: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self { files }
+    }
+
+    /// Returns the content of the file at the relative path, if it is embedded.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::EmbeddedDir;
+    ///
+    /// static ASSETS: EmbeddedDir = EmbeddedDir::new(&[("themes/dark.toml", b"bg = 0" as &[u8])]);
+    ///
+    /// assert_eq!(ASSETS.get("themes/dark.toml"), Some(&b"bg = 0"[..]));
+    /// assert_eq!(ASSETS.get("./themes/../themes/dark.toml"), Some(&b"bg = 0"[..]));
+    /// assert_eq!(ASSETS.get("themes"), None);
+    /// ```
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&'static [u8]> {
+        let key = key(path.as_ref())?;
+        self.files
+            .binary_search_by(|(file, _)| (*file).cmp(key.as_str()))
+            .ok()
+            .map(|index| self.files[index].1)
+    }
+
+    /// Checks to see if the relative path is a directory with embedded files in it.
+    pub fn is_dir(&self, path: impl AsRef<Path>) -> bool {
+        match key(path.as_ref()) {
+            Some(key) if key.is_empty() => !self.files.is_empty(),
+            Some(key) => self
+                .files
+                .iter()
+                .any(|(file, _)| in_dir(file, &key).is_some()),
+            None => false,
+        }
+    }
+
+    /// Returns the paths and content of every embedded file.
+    pub fn files(&self) -> impl Iterator<Item = (&'static str, &'static [u8])> {
+        self.files.iter().copied()
+    }
+
+    /// Returns the names of the entries of the directory at the relative path, along with
+    /// whether they are directories.
+    fn entries(&self, path: &Path) -> Vec<(String, bool)> {
+        let key = match key(path) {
+            Some(key) => key,
+            None => return Vec::new(),
+        };
+
+        let mut entries: Vec<(String, bool)> = Vec::new();
+        for (file, _) in self.files {
+            let rest = match in_dir(file, &key) {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let entry = match rest.split_once('/') {
+                Some((dir, _)) => (dir.to_string(), true),
+                None => (rest.to_string(), false),
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+}
+
+/// Returns the rest of the file path if the file is inside the directory.
+fn in_dir<'a>(file: &'a str, dir: &str) -> Option<&'a str> {
+    if dir.is_empty() {
+        return Some(file);
+    }
+    file.strip_prefix(dir)?.strip_prefix('/')
+}
+
+/// Turns a relative path into the `/` separated path it is embedded as.
+fn key(path: &Path) -> Option<String> {
+    let mut names = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => names.push(name.to_str()?),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                names.pop()?;
+            }
+            Component::Prefix(_) | Component::RootDir => return None,
+        }
+    }
+    Some(names.join("/"))
+}
+
+/// Writes the table of files for an [`EmbeddedDir`] to `$OUT_DIR/<out_file>`.  Meant to be
+/// called from a build script, with `dir` relative to the crate root.  Also tells cargo to
+/// run the build script again when anything in the directory changes.
+///
+/// See [`EmbeddedDir`] for an example.
+pub fn generate(dir: impl AsRef<Path>, out_file: impl AsRef<Path>) -> Result<()> {
+    let dir = dir.as_ref().canonicalize()?;
+    let out_dir = std::env::var_os("OUT_DIR")
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "OUT_DIR is not set"))?;
+
+    let mut files = Vec::new();
+    collect_files(&dir, &mut files)?;
+    let mut keyed_files = Vec::new();
+    for file in files {
+        let key = file
+            .strip_prefix(&dir)
+            .ok()
+            .and_then(key)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "file name is not UTF-8"))?;
+        keyed_files.push((key, file));
+    }
+    keyed_files.sort();
+
+    let mut table = String::from("&[\n");
+    for (key, file) in keyed_files {
+        let file = file
+            .to_str()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "file name is not UTF-8"))?;
+        let _ = writeln!(
+            table,
+            "    ({:?}, include_bytes!({:?}) as &[u8]),",
+            key, file
+        );
+    }
+    table.push(']');
+
+    write(Path::new(&out_dir).join(out_file), table)?;
+    println!("cargo:rerun-if-changed={}", dir.display());
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+impl Backend for EmbeddedDir {
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.get(path)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| not_found().into())
+    }
+
+    fn write(&self, _path: &Path, _content: &[u8], _write_mode: WriteMode) -> Result<()> {
+        Err(AppResError::ReadOnly)
+    }
+
+    fn list(&self, path: &Path) -> Result<Vec<DirEntry>> {
+        if !self.is_dir(path) {
+            return Err(not_found().into());
+        }
+        Ok(self
+            .entries(path)
+            .into_iter()
+            .map(|(name, is_dir)| DirEntry {
+                name: name.into(),
+                kind: if is_dir {
+                    EntryKind::Dir
+                } else {
+                    EntryKind::File
+                },
+                is_symlink: false,
+            })
+            .collect())
+    }
+
+    fn remove(&self, _path: &Path) -> Result<()> {
+        Err(AppResError::ReadOnly)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        if let Some(content) = self.get(path) {
+            return Ok(Metadata {
+                kind: EntryKind::File,
+                len: content.len() as u64,
+                modified: None,
+            });
+        }
+        if !self.is_dir(path) {
+            return Err(not_found().into());
+        }
+        Ok(Metadata {
+            kind: EntryKind::Dir,
+            len: 0,
+            modified: None,
+        })
+    }
+}
@@ -0,0 +1,303 @@
+use std::path::Path;
+
+use crate::{AppResError, Resources, Result, Value};
+
+/// Overrides values parsed from a file with environment variables.
+///
+/// A variable named `<PREFIX>_<KEY>` overrides the key `KEY`, and nested keys are separated
+/// by the separator (`__` by default), so `MYAPP_SERVER__PORT=8080` overrides
+/// `server.port`.  Keys are matched case insensitively against the keys already in the
+/// file and are lowercased otherwise.
+///
+/// Values are coerced to the type of the value they replace.  New keys become booleans
+/// (`true` or `false`), numbers, lists (`[a, b, c]`) or strings, in that order.  Lists are
+/// given either in brackets or, when replacing a list, as comma separated items.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use serde::Deserialize;
+///
+/// use appres::{EnvOverrides, Resources};
+///
+/// #[derive(Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     server: Server,
+/// }
+///
+/// let resources = Resources::new_app_relative_to_config("myapp").unwrap();
+///
+/// // Load config.yaml, letting MYAPP_SERVER__PORT=8080 override the port.
+/// let config: Config = resources
+///     .load_with_env("config.yaml", &EnvOverrides::new("MYAPP"))
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnvOverrides {
+    prefix: String,
+    separator: String,
+    vars: Option<Vec<(String, String)>>,
+}
+
+impl EnvOverrides {
+    /// Creates overrides for the environment variables starting with `<prefix>_`.  An empty
+    /// prefix takes every variable, without a leading `_`, so it is only accepted along with
+    /// [`EnvOverrides::with_vars`]; reading the process environment without a prefix fails
+    /// with [`AppResError::EmptyEnvPrefix`].
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            separator: String::from("__"),
+            vars: None,
+        }
+    }
+
+    /// Sets the separator between nested keys.  Defaults to `__`.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Reads the variables from the given pairs instead of the process environment.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::EnvOverrides;
+    ///
+    /// let overrides = EnvOverrides::new("MYAPP").with_vars(vec![
+    ///     ("MYAPP_SERVER__PORT", "8080"),
+    ///     ("MYAPP_SERVER__HOSTS", "a,b"),
+    ///     ("OTHER_PORT", "1"),
+    /// ]);
+    ///
+    /// let mut config = serde_json::json!({"server": {"port": 80, "hosts": []}});
+    /// overrides.apply(&mut config).unwrap();
+    /// assert_eq!(config, serde_json::json!({"server": {"port": 8080, "hosts": ["a", "b"]}}));
+    /// ```
+    pub fn with_vars<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.vars = Some(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Applies the overrides to a parsed value tree.  Fails with
+    /// [`AppResError::EnvKeyNotAMap`] if a variable overrides a key below a value that is
+    /// not a map, e.g. `MYAPP_PORT__X` when `port` is a number.
+    pub fn apply(&self, value: &mut Value) -> Result<()> {
+        self.apply_with_paths(value)?;
+        Ok(())
+    }
+
+    /// Applies the overrides to a parsed value tree and returns the key paths that were set,
+    /// spelled the way they are in the tree.
+    pub(crate) fn apply_with_paths(&self, value: &mut Value) -> Result<Vec<Vec<String>>> {
+        let mut vars = match &self.vars {
+            Some(vars) => vars.clone(),
+            None if self.prefix.is_empty() => return Err(AppResError::EmptyEnvPrefix),
+            None => std::env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+        };
+        // Apply parents before their children so that the children are not overwritten.
+        vars.sort();
+
+        let mut set_paths = vec![];
+        for (name, raw) in vars {
+            if let Some(key_path) = self.key_path(&name) {
+                let mut set_path_in_tree = vec![];
+                set_path(value, &key_path, &raw, &mut set_path_in_tree).map_err(|key| {
+                    AppResError::EnvKeyNotAMap {
+                        variable: name.clone(),
+                        key,
+                    }
+                })?;
+                set_paths.push(set_path_in_tree);
+            }
+        }
+        Ok(set_paths)
+    }
+
+    /// Applies the overrides to the value tree parsed from the file at the relative path and
+    /// deserializes the result.
+    pub(crate) fn apply_and_deserialize<T>(
+        &self,
+        resources: &Resources,
+        path: &Path,
+        mut value: Value,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.apply(&mut value)?;
+        resources.deserialize_value(path, value)
+    }
+
+    /// Splits a variable name into the key path it overrides, if it has the prefix.
+    fn key_path(&self, name: &str) -> Option<Vec<String>> {
+        let name = match self.prefix.as_str() {
+            "" => name,
+            prefix => name.strip_prefix(prefix)?.strip_prefix('_')?,
+        };
+        let key_path: Vec<String> = name
+            .split(self.separator.as_str())
+            .map(String::from)
+            .collect();
+
+        if key_path.iter().any(String::is_empty) {
+            return None;
+        }
+        Some(key_path)
+    }
+}
+
+/// Sets the value at the key path, pushing the keys it went through onto `set_path_in_tree`.
+/// Fails with the keys that lead to a value that is neither a map nor null, if the key path
+/// goes below one.
+fn set_path(
+    value: &mut Value,
+    key_path: &[String],
+    raw: &str,
+    set_path_in_tree: &mut Vec<String>,
+) -> std::result::Result<(), String> {
+    let (key, rest) = match key_path.split_first() {
+        Some(split) => split,
+        None => {
+            *value = coerce(raw, Some(value));
+            return Ok(());
+        }
+    };
+
+    if value.is_null() {
+        *value = Value::Object(Default::default());
+    }
+    let map = match value.as_object_mut() {
+        Some(map) => map,
+        None => return Err(set_path_in_tree.join(".")),
+    };
+
+    let existing_key = map
+        .keys()
+        .find(|existing| existing.eq_ignore_ascii_case(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_lowercase());
+    set_path_in_tree.push(existing_key.clone());
+
+    match map.get_mut(&existing_key) {
+        Some(child) => set_path(child, rest, raw, set_path_in_tree),
+        None if rest.is_empty() => {
+            map.insert(existing_key, coerce(raw, None));
+            Ok(())
+        }
+        None => {
+            let mut child = Value::Object(Default::default());
+            set_path(&mut child, rest, raw, set_path_in_tree)?;
+            map.insert(existing_key, child);
+            Ok(())
+        }
+    }
+}
+
+/// Turns a raw variable into a value, shaped after the value it replaces if there is one.
+fn coerce(raw: &str, existing: Option<&Value>) -> Value {
+    match existing {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        Some(Value::Bool(_)) => parse_bool(raw).unwrap_or_else(|| Value::String(raw.to_string())),
+        Some(Value::Number(_)) => {
+            parse_number(raw).unwrap_or_else(|| Value::String(raw.to_string()))
+        }
+        Some(Value::Array(items)) => {
+            let inner = raw.trim();
+            let inner = inner
+                .strip_prefix('[')
+                .and_then(|inner| inner.strip_suffix(']'))
+                .unwrap_or(inner);
+            Value::Array(
+                split_list(inner)
+                    .map(|item| coerce(item, items.first()))
+                    .collect(),
+            )
+        }
+        _ => parse_untyped(raw),
+    }
+}
+
+fn parse_untyped(raw: &str) -> Value {
+    if let Some(value) = parse_bool(raw).or_else(|| parse_number(raw)) {
+        return value;
+    }
+
+    let trimmed = raw.trim();
+    match trimmed
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+    {
+        Some(inner) => Value::Array(split_list(inner).map(parse_untyped).collect()),
+        None => Value::String(raw.to_string()),
+    }
+}
+
+fn split_list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn parse_bool(raw: &str) -> Option<Value> {
+    match raw.trim() {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+fn parse_number(raw: &str) -> Option<Value> {
+    let raw = raw.trim();
+    if let Ok(number) = raw.parse::<i64>() {
+        return Some(number.into());
+    }
+    if let Ok(number) = raw.parse::<u64>() {
+        return Some(number.into());
+    }
+    raw.parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .map(Value::from)
+}
+
+impl Resources {
+    /// Read a file from the resources directory, apply the environment variable overrides
+    /// and deserialize it.  The format is picked from the extension the same way as in
+    /// [`Resources::load`].
+    ///
+    /// See [`EnvOverrides`] for an example.
+    pub fn load_with_env<T>(&self, path: impl AsRef<Path>, env: &EnvOverrides) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let format = self.format_for(path)?;
+
+        let file_content = self.load_from_file(path)?;
+        let value = self.parse_value_as(format, path, file_content.as_bytes())?;
+        env.apply_and_deserialize(self, path, value)
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
 /// Grouping all errors together to simplify error handling.
@@ -6,8 +9,21 @@ pub enum AppResError {
     /// Could not find the config directory.
     #[error("cannot find config dir")]
     ConfigDirNotFound,
-    /// Could not parse the json when serializing or deserializing.
-    #[cfg(feature = "json_resources")]
+    /// Could not find the data directory.
+    #[error("cannot find data dir")]
+    DataDirNotFound,
+    /// Could not find the cache directory.
+    #[error("cannot find cache dir")]
+    CacheDirNotFound,
+    /// Could not find the state directory.
+    #[error("cannot find state dir")]
+    StateDirNotFound,
+    /// Could not find the runtime directory.
+    #[error("cannot find runtime dir")]
+    RuntimeDirNotFound,
+    /// Could not parse the json when serializing or deserializing, or could not convert
+    /// between an object and a [`Value`](crate::Value).
+    #[cfg(feature = "serde_resources")]
     #[error(transparent)]
     InvalidJson(#[from] serde_json::Error),
     /// Could not parse the toml when deserializing.
@@ -25,7 +41,312 @@ pub enum AppResError {
     /// Could not read, write, or access files or directories on the filesystem.
     #[error(transparent)]
     IOError(#[from] std::io::Error),
+    /// The file extension does not match any known format.
+    #[error("no known format for {}", .0.display())]
+    UnknownFormat(PathBuf),
+    /// The file extension matches a format whose feature is not enabled.
+    #[error("the {format} format requires the {feature} feature")]
+    FormatNotEnabled {
+        /// Name of the format.
+        format: &'static str,
+        /// Feature that needs to be enabled to use the format.
+        feature: &'static str,
+    },
+    /// Could not parse the content with a [`Format`](crate::Format) implemented outside of
+    /// this crate.
+    #[error("invalid {format}: {source}")]
+    InvalidFormat {
+        /// Name of the format.
+        format: String,
+        /// Error reported by the format.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// None of the layers of a [`LayeredResources`](crate::LayeredResources) has the file.
+    #[error("cannot find {} in any layer", .0.display())]
+    NotFoundInLayers(PathBuf),
+    /// Environment variable overrides were to be read from the process environment without
+    /// a prefix, which would take every variable.
+    #[error("environment variable overrides need a prefix")]
+    EmptyEnvPrefix,
+    /// An environment variable overrides a key below a value that is not a map.
+    #[error("{variable} overrides a key below {key}, which is not a map")]
+    EnvKeyNotAMap {
+        /// Name of the environment variable.
+        variable: String,
+        /// The key of the value that is not a map.
+        key: String,
+    },
+    /// The version stored in a versioned file is not a positive number.
+    #[error("invalid version: {0}")]
+    InvalidVersion(String),
+    /// The version stored in a versioned file is newer than the migrations know about.
+    #[error("version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion {
+        /// Version stored in the file.
+        found: u64,
+        /// Current version of the migrations.
+        supported: u64,
+    },
+    /// There is no migration that upgrades a file from this version.
+    #[error("no migration from version {0}")]
+    MissingMigration(u64),
+    /// The top level of a versioned file is not a map that the version can be stored in.
+    #[error("the top level of a versioned file is not a map")]
+    RootNotAMap,
+    /// Another file with the same stem comes first in the directory read by
+    /// [`Resources::load_all`](crate::Resources::load_all).
+    #[error("another file with the stem {0} comes first")]
+    DuplicateStem(String),
     /// Unable to retrieve the parent for a directory.
     #[error("there is no parent for this directory")]
     NoParent,
+    /// The path to write a file to does not end in a file name, e.g. `/` or `..`.
+    #[error("{} is not a path to a file", .0.display())]
+    NotAFilePath(PathBuf),
+    /// Could not parse a glob pattern.
+    #[error("invalid glob pattern: {0}")]
+    InvalidGlob(#[from] glob::PatternError),
+    /// The backend, e.g. resources embedded into the binary, cannot be written to.
+    #[error("the resources are read only")]
+    ReadOnly,
+    /// Another process held the lock on a file for longer than the timeout.
+    #[error("timed out after {timeout:?} waiting for the lock on {}", .path.display())]
+    LockTimeout {
+        /// The file that could not be locked.
+        path: PathBuf,
+        /// How long was waited for the lock.
+        timeout: std::time::Duration,
+    },
+    /// The file does not have as many backups as asked for.
+    #[error("{} has no backup {n}", .path.display())]
+    NoBackup {
+        /// The file that was to be restored.
+        path: PathBuf,
+        /// The backup that was asked for, starting at 1.
+        n: usize,
+    },
+    /// A loaded object violates invariants checked by [`Validate`](crate::Validate).
+    #[cfg(feature = "serde_resources")]
+    #[error("invalid content: {0}")]
+    Validation(crate::ValidationErrors),
+    /// A relative path leaves the resources directory while strict paths are enabled.
+    #[error("{} is outside of {}", .path.display(), .root.display())]
+    PathOutsideRoot {
+        /// The relative path that was given.
+        path: PathBuf,
+        /// The resources directory.
+        root: PathBuf,
+    },
+    /// Another error, along with the file and operation that caused it.
+    #[error(
+        "cannot {operation} {}{}: {source}",
+        .path.display(),
+        .location.as_ref().map(ToString::to_string).unwrap_or_default()
+    )]
+    Context {
+        /// Absolute path of the file.
+        path: PathBuf,
+        /// What was being done with the file.
+        operation: Operation,
+        /// Where in the file parsing failed, for parse errors that report it.
+        location: Option<Location>,
+        /// The underlying error.
+        source: Box<AppResError>,
+    },
+}
+
+/// What was being done with a file when an error happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Reading the file.
+    Read,
+    /// Writing the file.
+    Write,
+    /// Parsing the content of the file.
+    Parse,
+    /// Serializing content for the file.
+    Serialize,
+    /// Locking the file.
+    Lock,
+    /// Validating the content of the file.
+    Validate,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Read => "read",
+            Operation::Write => "write",
+            Operation::Parse => "parse",
+            Operation::Serialize => "serialize",
+            Operation::Lock => "lock",
+            Operation::Validate => "validate",
+        })
+    }
+}
+
+/// Where in a file parsing failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number, starting at 1.
+    pub column: usize,
+    /// The offending line of the file.
+    pub snippet: String,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+impl AppResError {
+    /// Returns the absolute path of the file that caused the error, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            AppResError::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns what was being done with the file that caused the error, if known.
+    pub fn operation(&self) -> Option<Operation> {
+        match self {
+            AppResError::Context { operation, .. } => Some(*operation),
+            _ => None,
+        }
+    }
+
+    /// Returns where in the file parsing failed, if known.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            AppResError::Context { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the violations found by [`Validate`](crate::Validate), if that is what
+    /// caused the error.
+    #[cfg(feature = "serde_resources")]
+    pub fn validation_errors(&self) -> Option<&crate::ValidationErrors> {
+        match self.without_context() {
+            AppResError::Validation(errors) => Some(errors),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without the file and operation that caused it.
+    pub fn without_context(&self) -> &AppResError {
+        match self {
+            AppResError::Context { source, .. } => source.without_context(),
+            error => error,
+        }
+    }
+
+    /// Checks whether the error was caused by a missing file.
+    pub fn is_not_found(&self) -> bool {
+        match self.without_context() {
+            AppResError::IOError(err) => err.kind() == std::io::ErrorKind::NotFound,
+            _ => false,
+        }
+    }
+
+    /// Adds the file and operation to the error, unless it already has them.
+    pub(crate) fn with_context(self, path: &Path, operation: Operation) -> Self {
+        self.with_location(path, operation, None)
+    }
+
+    /// Adds the file to a parse error, along with where in `content` parsing failed.
+    #[cfg(feature = "serde_resources")]
+    pub(crate) fn with_parse_context(self, path: &Path, content: &[u8]) -> Self {
+        let location = self.line_column().map(|(line, column)| Location {
+            line,
+            column,
+            snippet: String::from_utf8_lossy(content)
+                .lines()
+                .nth(line - 1)
+                .unwrap_or_default()
+                .to_string(),
+        });
+        self.with_location(path, Operation::Parse, location)
+    }
+
+    fn with_location(self, path: &Path, operation: Operation, location: Option<Location>) -> Self {
+        if let AppResError::Context { .. } = self {
+            return self;
+        }
+
+        AppResError::Context {
+            path: absolute(path),
+            operation,
+            location,
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the 1-based line and column reported by a parse error.
+    #[cfg(feature = "serde_resources")]
+    fn line_column(&self) -> Option<(usize, usize)> {
+        match self {
+            AppResError::InvalidJson(err) if err.line() > 0 => Some((err.line(), err.column())),
+            #[cfg(feature = "toml_resources")]
+            AppResError::InvalidTomlDeserialization(err) => {
+                err.line_col().map(|(line, column)| (line + 1, column + 1))
+            }
+            #[cfg(feature = "yaml_resources")]
+            AppResError::InvalidYaml(err) => err
+                .location()
+                .map(|location| (location.line(), location.column())),
+            _ => None,
+        }
+        .filter(|(line, _)| *line > 0)
+    }
+
+    /// Creates an [`AppResError::InvalidFormat`] for an error reported by the named format.
+    pub fn invalid_format(
+        format: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        AppResError::InvalidFormat {
+            format: format.into(),
+            source: source.into(),
+        }
+    }
+}
+
+/// Adds file and operation context to the errors of results.
+pub(crate) trait ResultExt<T> {
+    /// Adds the file and operation to the error.
+    fn context(self, path: &Path, operation: Operation) -> Result<T, AppResError>;
+    /// Adds the file to a parse error, along with where in `content` parsing failed.
+    #[cfg(feature = "serde_resources")]
+    fn parse_context(self, path: &Path, content: &[u8]) -> Result<T, AppResError>;
+}
+
+impl<T, E> ResultExt<T> for Result<T, E>
+where
+    E: Into<AppResError>,
+{
+    fn context(self, path: &Path, operation: Operation) -> Result<T, AppResError> {
+        self.map_err(|err| err.into().with_context(path, operation))
+    }
+
+    #[cfg(feature = "serde_resources")]
+    fn parse_context(self, path: &Path, content: &[u8]) -> Result<T, AppResError> {
+        self.map_err(|err| err.into().with_parse_context(path, content))
+    }
+}
+
+/// Makes the path absolute relative to the current directory, without touching the
+/// filesystem otherwise.
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    match std::env::current_dir() {
+        Ok(current_dir) => current_dir.join(path),
+        Err(_) => path.to_path_buf(),
+    }
 }
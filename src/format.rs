@@ -0,0 +1,261 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::ResultExt;
+use crate::{AppResError, Operation, Resources, Result, Value};
+
+/// A codec that turns objects into bytes and back.  The json, toml and yaml modules each
+/// provide an implementation, and other crates can implement it for their own formats.
+///
+/// Objects are passed through [`erased_serde`] so that formats can be registered on a
+/// [`Resources`] and picked at runtime, while still serializing and deserializing each type
+/// directly rather than through an intermediate value.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::{AppResError, Format, Resources, Result};
+///
+/// /// Json stored in files with a `.cfg` extension.
+/// struct CfgFormat;
+///
+/// impl Format for CfgFormat {
+///     fn name(&self) -> &str {
+///         "cfg"
+///     }
+///
+///     fn extensions(&self) -> &[&str] {
+///         &["cfg"]
+///     }
+///
+///     fn serialize(&self, thing: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+///         serde_json::to_vec(thing).map_err(|err| AppResError::invalid_format(self.name(), err))
+///     }
+///
+///     fn deserialize_with(
+///         &self,
+///         content: &[u8],
+///         visit: &mut dyn FnMut(&mut dyn erased_serde::Deserializer<'_>) -> Result<()>,
+///     ) -> Result<()> {
+///         let mut deserializer = serde_json::Deserializer::from_slice(content);
+///         visit(&mut <dyn erased_serde::Deserializer>::erase(&mut deserializer))?;
+///         deserializer
+///             .end()
+///             .map_err(|err| AppResError::invalid_format(self.name(), err))
+///     }
+/// }
+///
+/// // Load the settings.cfg file in the assets folder with the format directly...
+/// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+/// let settings: Vec<String> = resources.load_with(&CfgFormat, "settings.cfg").unwrap();
+///
+/// // ...or register it so that `load` and `save` recognize the extension.
+/// let resources = resources.with_format(CfgFormat);
+/// let settings: Vec<String> = resources.load("settings.cfg").unwrap();
+/// ```
+pub trait Format {
+    /// Name of the format, used in error messages.
+    fn name(&self) -> &str;
+    /// File extensions (without the leading dot) handled by this format.
+    fn extensions(&self) -> &[&str];
+    /// Serialize an object into bytes.
+    fn serialize(&self, thing: &dyn erased_serde::Serialize) -> Result<Vec<u8>>;
+    /// Creates a deserializer over the bytes and hands it to `visit`, which deserializes the
+    /// requested object from it.
+    fn deserialize_with(
+        &self,
+        content: &[u8],
+        visit: &mut dyn FnMut(&mut dyn erased_serde::Deserializer<'_>) -> Result<()>,
+    ) -> Result<()>;
+}
+
+/// Deserializes an object with the format through [`Format::deserialize_with`].
+pub(crate) fn deserialize_erased<F, T>(format: &F, content: &[u8]) -> Result<T>
+where
+    F: Format + ?Sized,
+    T: serde::de::DeserializeOwned,
+{
+    let mut thing = None;
+    format.deserialize_with(content, &mut |deserializer| {
+        let deserialized = erased_serde::deserialize(deserializer)
+            .map_err(|err| AppResError::invalid_format(format.name(), err))?;
+        thing = Some(deserialized);
+        Ok(())
+    })?;
+    thing.ok_or_else(|| AppResError::invalid_format(format.name(), "nothing was deserialized"))
+}
+
+/// A [`Format`] stored in a [`Resources`] and picked at runtime.
+pub(crate) trait ErasedFormat: Send + Sync {
+    fn name(&self) -> &str;
+    fn extensions(&self) -> &[&str];
+    fn as_format(&self) -> &dyn Format;
+    fn parse_value(&self, content: &[u8]) -> Result<Value>;
+    /// Adds the values at the key paths to the content, keeping its comments and
+    /// formatting.  Returns `None` if the format cannot edit content that way.
+    fn add_keys(&self, content: &[u8], added: &[(Vec<String>, Value)]) -> Option<Result<Vec<u8>>>;
+}
+
+impl<F> ErasedFormat for F
+where
+    F: Format + Send + Sync,
+{
+    fn name(&self) -> &str {
+        Format::name(self)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        Format::extensions(self)
+    }
+
+    fn as_format(&self) -> &dyn Format {
+        self
+    }
+
+    fn parse_value(&self, content: &[u8]) -> Result<Value> {
+        deserialize_erased(self, content)
+    }
+
+    fn add_keys(&self, _: &[u8], _: &[(Vec<String>, Value)]) -> Option<Result<Vec<u8>>> {
+        None
+    }
+}
+
+impl dyn ErasedFormat {
+    pub(crate) fn handles(&self, path: &Path) -> bool {
+        match path.extension().and_then(OsStr::to_str) {
+            Some(extension) => self
+                .extensions()
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(extension)),
+            None => false,
+        }
+    }
+}
+
+/// The formats registered on a [`Resources`] with [`Resources::with_format`].
+#[derive(Clone, Default)]
+pub(crate) struct FormatRegistry {
+    formats: Vec<Arc<dyn ErasedFormat>>,
+}
+
+impl FormatRegistry {
+    pub(crate) fn register(&mut self, format: Arc<dyn ErasedFormat>) {
+        self.formats.push(format);
+    }
+
+    /// Returns the most recently registered format that handles the path's extension.
+    pub(crate) fn find(&self, path: &Path) -> Option<&dyn ErasedFormat> {
+        self.formats
+            .iter()
+            .rev()
+            .map(|format| format.as_ref())
+            .find(|format| format.handles(path))
+    }
+}
+
+impl fmt::Debug for FormatRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.formats.iter().map(|format| format.name()))
+            .finish()
+    }
+}
+
+impl Resources {
+    /// Registers a format so that [`Resources::load`] and [`Resources::save`] recognize its
+    /// extensions.  Formats registered later take precedence, including over the built-in
+    /// json, toml and yaml formats.
+    ///
+    /// See [`Format`] for an example.
+    pub fn with_format<F>(mut self, format: F) -> Self
+    where
+        F: Format + Send + Sync + 'static,
+    {
+        self.formats.register(Arc::new(format));
+        self
+    }
+
+    /// Read a file from the resources directory and deserialize it with the given format.
+    ///
+    /// See [`Format`] for an example.
+    pub fn load_with<F, T>(&self, format: &F, path: impl AsRef<Path>) -> Result<T>
+    where
+        F: Format,
+        T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let file_content = self.load_bytes(path)?;
+        self.deserialize_with(format, path, &file_content)
+    }
+
+    /// Serialize an object with the given format and write it to a path relative from the
+    /// resources directory.
+    ///
+    /// See [`Format`] for an example.
+    pub fn save_with<F, C>(&self, format: &F, path: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        F: Format + ?Sized,
+        C: serde::Serialize + ?Sized,
+    {
+        let path = path.as_ref();
+        self.save_to_file(path, self.serialize_with(format, path, thing)?)
+    }
+
+    /// Serializes an object with the given format for the file at the relative path.
+    pub(crate) fn serialize_with<F, C>(&self, format: &F, path: &Path, thing: &C) -> Result<Vec<u8>>
+    where
+        F: Format + ?Sized,
+        C: serde::Serialize + ?Sized,
+    {
+        format
+            .serialize(&thing)
+            .context(&self.get_file_path(path), Operation::Serialize)
+    }
+
+    /// Deserializes the content of the file at the relative path with the given format.
+    pub(crate) fn deserialize_with<F, T>(
+        &self,
+        format: &F,
+        path: &Path,
+        content: &[u8],
+    ) -> Result<T>
+    where
+        F: Format + ?Sized,
+        T: serde::de::DeserializeOwned,
+    {
+        deserialize_erased(format, content).parse_context(&self.get_file_path(path), content)
+    }
+
+    /// Parses the content of the file at the relative path into a value tree.
+    pub(crate) fn parse_value_as(
+        &self,
+        format: &dyn ErasedFormat,
+        path: &Path,
+        content: &[u8],
+    ) -> Result<Value> {
+        format
+            .parse_value(content)
+            .parse_context(&self.get_file_path(path), content)
+    }
+
+    /// Deserializes the value tree parsed from the file at the relative path.
+    pub(crate) fn deserialize_value<T>(&self, path: &Path, value: Value) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::from_value(value).context(&self.get_file_path(path), Operation::Parse)
+    }
+
+    /// Turns an object into a value tree for the file at the relative path.
+    pub(crate) fn serialize_value<C>(&self, path: &Path, thing: &C) -> Result<Value>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        serde_json::to_value(thing).context(&self.get_file_path(path), Operation::Serialize)
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use crate::error::ResultExt;
+use crate::format::ErasedFormat;
+use crate::value::fill_missing;
+use crate::{Resources, Result};
+
+impl Resources {
+    /// Read a file from the resources directory and deserialize it, writing `T::default()`
+    /// to the file first if it does not exist yet.  The format is picked from the extension
+    /// the same way as in [`Resources::load`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load config.toml, creating it on first run.
+    /// let config: Config = resources.load_or_init("config.toml").unwrap();
+    /// ```
+    pub fn load_or_init<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_with(path, T::default(), false)
+    }
+
+    /// Read a file from the resources directory and deserialize it, writing `default` to
+    /// the file first if it does not exist yet.  The format is picked from the extension the
+    /// same way as in [`Resources::load`].
+    ///
+    /// If `fill_missing` is set and the file exists, keys of `default` that are missing from
+    /// the file (e.g. fields added in a newer release) are added to it and the file is
+    /// written back.  Values already in the file are left untouched, and so are keys that
+    /// `T` does not know.  Toml files (with the toml_edit_resources feature) and yaml files
+    /// (with the yaml_edit_resources feature) keep their comments and formatting, while
+    /// files in other formats are written anew.
+    ///
+    /// The file is created or filled while holding an exclusive lock on it, even if locking
+    /// is not enabled with [`Resources::with_locking`], so that instances of an application
+    /// starting at the same time do not overwrite each other's files.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    ///     #[serde(default)]
+    ///     retries: u32,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load config.yaml, creating it on first run and adding the retries field to files
+    /// // written by older releases.
+    /// let default = Config { stuff: String::from("Hello World"), retries: 3 };
+    /// let config: Config = resources.load_or_init_with("config.yaml", default, true).unwrap();
+    /// ```
+    pub fn load_or_init_with<T>(
+        &self,
+        path: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let format = self.format_for(path)?;
+        self.load_or_init_as(format, path, default, fill_missing)
+    }
+
+    /// Implements `load_or_init` for the given format.
+    pub(crate) fn load_or_init_as<T>(
+        &self,
+        format: &dyn ErasedFormat,
+        path: &Path,
+        default: T,
+        fill_missing_keys: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let locking = self.locking.unwrap_or_default();
+        let (thing, _) = self.modify_file(
+            path,
+            Some(locking),
+            Ok,
+            |content: &mut Option<Vec<u8>>| {
+                let file_content = match content {
+                    Some(file_content) => file_content,
+                    None => {
+                        let serialized = self.serialize_with(format.as_format(), path, &default)?;
+                        *content = Some(serialized);
+                        return Ok(default);
+                    }
+                };
+
+                if fill_missing_keys {
+                    let mut value = self.parse_value_as(format, path, file_content)?;
+                    let added = fill_missing(&mut value, self.serialize_value(path, &default)?);
+                    if !added.is_empty() {
+                        *file_content = match format.add_keys(file_content, &added) {
+                            Some(edited) => {
+                                edited.parse_context(&self.get_file_path(path), file_content)?
+                            }
+                            None => self.serialize_with(format.as_format(), path, &value)?,
+                        };
+                    }
+                }
+                self.deserialize_with(format.as_format(), path, file_content)
+            },
+            |content| Ok(content.clone().unwrap_or_default()),
+        )?;
+        Ok(thing)
+    }
+}
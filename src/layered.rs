@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::value::{get_path, leaf_paths, merge};
+use crate::{AppResError, EnvOverrides, Resources, Result, Value};
+
+/// A stack of resource directories that are read as one, e.g. defaults shipped next to the
+/// executable, then the user's config directory, then a project-local directory.  Loading a
+/// file reads it from every layer that has it and deep merges the results: maps are merged
+/// key by key and everything else is overridden by later layers.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use serde::Deserialize;
+///
+/// use appres::{LayeredResources, Resources};
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// // Later layers override earlier ones.
+/// let resources = LayeredResources::new(vec![
+///     Resources::new_dir_relative_to_executable("defaults").unwrap(),
+///     Resources::new_app_relative_to_config("projectile").unwrap(),
+///     Resources::new(".projectile"),
+/// ]);
+///
+/// // Load config.toml from every layer and merge them.
+/// let config: Config = resources.load("config.toml").unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LayeredResources {
+    layers: Vec<Resources>,
+    env: Option<EnvOverrides>,
+}
+
+/// A value merged from several layers, along with the layer each key came from.
+#[derive(Clone, Debug)]
+pub struct Layered<T> {
+    /// The merged value.
+    pub value: T,
+    /// Maps the dotted key path of every leaf of the merged value (e.g. `server.port`) to
+    /// the index of the layer it came from.
+    pub sources: BTreeMap<String, usize>,
+}
+
+impl<T> Layered<T> {
+    /// Source index reported for keys set by the environment variable overrides.
+    pub const ENV_SOURCE: usize = usize::MAX;
+
+    /// Returns the index of the layer that the key at the dotted key path came from.
+    pub fn source(&self, key: &str) -> Option<usize> {
+        self.sources.get(key).copied()
+    }
+}
+
+impl LayeredResources {
+    /// Creates layered resources from layers ordered from lowest to highest priority.
+    pub fn new(layers: impl IntoIterator<Item = Resources>) -> Self {
+        Self {
+            layers: layers.into_iter().collect(),
+            env: None,
+        }
+    }
+
+    /// Adds a layer on top of the existing layers.
+    pub fn with_layer(mut self, layer: Resources) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Applies environment variable overrides on top of the merged layers.  Keys that are
+    /// overridden are reported as coming from [`Layered::ENV_SOURCE`].
+    pub fn with_env_overrides(mut self, env: EnvOverrides) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Returns the layers ordered from lowest to highest priority.
+    pub fn layers(&self) -> &[Resources] {
+        &self.layers
+    }
+
+    /// Reads the file at the path from every layer that has it, merges the results and
+    /// deserializes them.  The format is picked from the extension the same way as in
+    /// [`Resources::load`].  Returns [`AppResError::NotFoundInLayers`] if no layer has
+    /// the file.
+    pub fn load<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(self.load_with_sources(path)?.value)
+    }
+
+    /// Same as [`LayeredResources::load`], but also reports which layer each key of the
+    /// merged value came from.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{LayeredResources, Resources, Value};
+    ///
+    /// let resources = LayeredResources::new(vec![
+    ///     Resources::new_dir_relative_to_executable("defaults").unwrap(),
+    ///     Resources::new_app_relative_to_config("projectile").unwrap(),
+    /// ]);
+    ///
+    /// // Find out whether the user overrode the port.
+    /// let config = resources.load_with_sources::<Value>("config.yaml").unwrap();
+    /// let overridden = config.source("server.port") == Some(1);
+    /// ```
+    pub fn load_with_sources<T>(&self, path: impl AsRef<Path>) -> Result<Layered<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let (top_layer, Layered { value, sources }) = self.load_value(path)?;
+        Ok(Layered {
+            value: top_layer.deserialize_value(path, value)?,
+            sources,
+        })
+    }
+
+    /// Returns the merged value along with the highest layer that has the file.
+    fn load_value(&self, path: &Path) -> Result<(&Resources, Layered<Value>)> {
+        let mut layer_values = vec![];
+        for (index, layer) in self.layers.iter().enumerate() {
+            match layer.load_from_file(path) {
+                Ok(content) => {
+                    let format = layer.format_for(path)?;
+                    let value = layer.parse_value_as(format, path, content.as_bytes())?;
+                    layer_values.push((index, value));
+                }
+                Err(err) if err.is_not_found() => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let (mut merged, top_layer) = match (layer_values.first(), layer_values.last()) {
+            (Some((_, value)), Some((index, _))) => (value.clone(), &self.layers[*index]),
+            _ => return Err(AppResError::NotFoundInLayers(path.to_path_buf())),
+        };
+        for (_, value) in layer_values.iter().skip(1) {
+            merge(&mut merged, value.clone());
+        }
+        let overridden = match &self.env {
+            Some(env) => env.apply_with_paths(&mut merged)?,
+            None => vec![],
+        };
+
+        let sources = leaf_paths(&merged)
+            .into_iter()
+            .filter_map(|key_path| {
+                if overridden
+                    .iter()
+                    .any(|overridden| key_path.starts_with(overridden))
+                {
+                    return Some((key_path.join("."), Layered::<Value>::ENV_SOURCE));
+                }
+                let (index, _) = layer_values
+                    .iter()
+                    .rev()
+                    .find(|(_, value)| get_path(value, &key_path).is_some())?;
+                Some((key_path.join("."), *index))
+            })
+            .collect();
+
+        Ok((
+            top_layer,
+            Layered {
+                value: merged,
+                sources,
+            },
+        ))
+    }
+}
@@ -1,18 +1,85 @@
+#[cfg(feature = "tokio_resources")]
+mod async_resources;
+mod atomic;
+mod backend;
+mod backup;
+mod embed;
+#[cfg(feature = "serde_resources")]
+mod env;
 mod error;
+#[cfg(feature = "serde_resources")]
+mod format;
+#[cfg(feature = "serde_resources")]
+mod init;
+#[cfg(feature = "serde_resources")]
+mod layered;
+mod list;
+#[cfg(feature = "serde_resources")]
+mod load_all;
+mod lock;
+#[cfg(feature = "serde_resources")]
+mod migrate;
+#[cfg(feature = "mmap_resources")]
+mod mmap;
 mod resource_types;
+mod sandbox;
+mod stream;
+#[cfg(feature = "serde_resources")]
+mod update;
+#[cfg(feature = "serde_resources")]
+mod validate;
+#[cfg(feature = "serde_resources")]
+mod value;
+#[cfg(feature = "serde_resources")]
+mod watch;
 
-use std::fs::{create_dir_all, read_to_string, write};
+use std::fs::read_to_string;
+use std::io::{Error, ErrorKind};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use dirs::config_dir;
+use dirs::{cache_dir, config_dir, data_dir, runtime_dir, state_dir};
 
-pub use error::AppResError;
+use crate::error::ResultExt;
+use crate::lock::LockKind;
+
+#[cfg(feature = "tokio_resources")]
+pub use async_resources::AsyncResources;
+pub use atomic::WriteMode;
+pub use backend::{Backend, DirEntry, FsBackend, MemoryBackend, Metadata, OverlayBackend};
+pub use backup::BackupPolicy;
+pub use embed::{generate, EmbeddedDir};
+#[cfg(feature = "serde_resources")]
+pub use env::EnvOverrides;
+pub use error::{AppResError, Location, Operation};
+#[cfg(feature = "serde_resources")]
+pub use format::Format;
+#[cfg(feature = "serde_resources")]
+pub use layered::{Layered, LayeredResources};
+pub use list::{EntryKind, ListOptions};
+#[cfg(feature = "serde_resources")]
+pub use load_all::LoadedDir;
+pub use lock::{FileLock, LockOptions};
+#[cfg(feature = "serde_resources")]
+pub use migrate::Migrations;
+#[cfg(feature = "mmap_resources")]
+pub use mmap::MappedFile;
 #[cfg(feature = "json_resources")]
 pub use resource_types::json;
 #[cfg(feature = "toml_resources")]
 pub use resource_types::toml;
 #[cfg(feature = "yaml_resources")]
 pub use resource_types::yaml;
+pub use stream::ResourceWriter;
+#[cfg(feature = "serde_resources")]
+pub use validate::{Validate, ValidationErrors, Violation};
+
+#[cfg(feature = "serde_resources")]
+pub use watch::{WatchOptions, Watcher};
+
+/// An untyped tree of parsed values, used to move content between formats.
+#[cfg(feature = "serde_resources")]
+pub use serde_json::Value;
 
 /// A shorthand for when the error is of type [`AppResError`].
 pub type Result<T> = std::result::Result<T, AppResError>;
@@ -48,10 +115,25 @@ pub type Result<T> = std::result::Result<T, AppResError>;
 ///   `TomlResourcesExt` trait) and extra functions for working with toml files.
 /// - **yaml_resources**: Enabling this feature gives you extra methods (through the
 ///   `YamlResourcesExt` trait) and extra functions for working with yaml files.
+/// - **json_schema_resources**: Enables `json_resources`, lets you check json files against
+///   a JSON Schema and lets you save json files along with a JSON Schema generated with
+///   `schemars`, so that editors offer completion.
+/// - **toml_edit_resources**: Enables `toml_resources` and lets you edit toml files key by
+///   key while keeping their comments and formatting.
+/// - **yaml_edit_resources**: Enables `yaml_resources` and lets you edit yaml files key by
+///   key while keeping their comments and formatting.
+/// - **serde_resources**: Enabled by all of the above.  Gives you the [`Format`] trait
+///   for plugging in your own formats.
+/// - **mmap_resources**: Enabling this feature gives you `Resources::load_mmap` for
+///   mapping large files into memory.
+/// - **tokio_resources**: Enabling this feature gives you `AsyncResources`, an async
+///   version of [`Resources`] for tokio, including the methods of the extension traits.
 ///
 /// For example, if you enable the `yaml_resources` feature in Cargo.toml...
 ///
 /// ```no_run
+/// # #[cfg(feature = "yaml_resources")]
+/// # {
 /// use appres::Resources;
 /// use appres::yaml::YamlResourcesExt;
 ///
@@ -71,12 +153,39 @@ pub type Result<T> = std::result::Result<T, AppResError>;
 ///
 /// // Check for the presence of the list.yaml file
 /// assert!(resources.has_file("list.yaml"));
+/// # }
 /// ```
+///
+/// # Writing files
+///
+/// Every write goes through an atomic write by default: the content is written to a
+/// temporary file next to the destination, synced, and renamed over the destination.  Use
+/// [`Resources::with_write_mode`] with [`WriteMode::Direct`] to opt out.
+/// Use [`Resources::with_backups`] to keep the previous versions of files that are
+/// overwritten.
+///
+/// # Untrusted paths
+///
+/// Relative paths are simply joined to the directory, so `../secret` or an absolute path
+/// reaches outside of it.  Use [`Resources::with_strict_paths`] when paths come from user
+/// input.
+///
+/// # Concurrent access
+///
+/// Nothing stops two processes from saving the same file at the same time, and the last
+/// one to save wins.  Use [`Resources::with_locking`] to lock files while they are loaded
+/// and saved, and `Resources::with_locked` to change a file without losing the changes
+/// of other processes.
 #[derive(Clone, Debug)]
 pub struct Resources {
     path: PathBuf,
-    #[cfg(feature = "toml_resources")]
-    resources: Vec<String>,
+    write_mode: WriteMode,
+    strict_paths: bool,
+    locking: Option<LockOptions>,
+    backups: BackupPolicy,
+    backend: Arc<dyn Backend>,
+    #[cfg(feature = "serde_resources")]
+    formats: format::FormatRegistry,
 }
 
 impl Resources {
@@ -97,13 +206,41 @@ impl Resources {
     /// let resources = Resources::new(root);
     /// ```
     pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
         Self {
-            path: path.into(),
-            #[cfg(feature = "toml_resources")]
-            resources: vec![],
+            backend: Arc::new(FsBackend::new(path.clone())),
+            path,
+            write_mode: WriteMode::default(),
+            strict_paths: false,
+            locking: None,
+            backups: BackupPolicy::default(),
+            #[cfg(feature = "serde_resources")]
+            formats: format::FormatRegistry::default(),
         }
     }
 
+    /// Creates a resource manager that only reads files embedded into the binary.  Writing
+    /// files fails with [`AppResError::ReadOnly`].  See [`EmbeddedDir`] for how to embed a
+    /// directory.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::EmbeddedDir;
+    /// use appres::Resources;
+    ///
+    /// static ASSETS: EmbeddedDir = EmbeddedDir::new(&[("greeting.txt", b"Hello" as &[u8])]);
+    ///
+    /// let resources = Resources::new_embedded(&ASSETS);
+    /// assert_eq!(resources.load_from_file("greeting.txt").unwrap(), "Hello");
+    /// assert!(resources.save_to_file("greeting.txt", "Bye").is_err());
+    /// ```
+    pub fn new_embedded(embedded: &'static EmbeddedDir) -> Self {
+        Self::new(PathBuf::new()).with_backend(*embedded)
+    }
+
     /// Creates a resource manager for the config directory.  An error may be returned if
     /// the config path cannot be retrieved.
     ///
@@ -196,6 +333,315 @@ impl Resources {
         Ok(Resources::new(dir_path))
     }
 
+    /// Creates a resource manager for the data directory.  An error may be returned if
+    /// the data path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the data directory.
+    /// let resources = Resources::new_relative_to_data().unwrap();
+    /// ```
+    pub fn new_relative_to_data() -> Result<Self> {
+        let data_dir_path = get_data_path()?;
+        Ok(Resources::new(data_dir_path))
+    }
+
+    /// Creates a resource manager for the specified app in the data directory.  An
+    /// error may be returned if the data path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile app in the data directory.
+    /// let resources = Resources::new_app_relative_to_data("projectile").unwrap();
+    /// ```
+    pub fn new_app_relative_to_data(app_name: impl AsRef<str>) -> Result<Self> {
+        Resources::new_dir_relative_to_data(app_name.as_ref())
+    }
+
+    /// Creates a resource manager for the specified directory in the data directory.
+    /// An error may be returned if the data path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile dir in the data directory.
+    /// let resources = Resources::new_dir_relative_to_data("projectile").unwrap();
+    /// ```
+    pub fn new_dir_relative_to_data(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut dir_path = get_data_path()?;
+        dir_path.push(dir);
+        Ok(Resources::new(dir_path))
+    }
+
+    /// Creates a resource manager for the cache directory.  An error may be returned if
+    /// the cache path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the cache directory.
+    /// let resources = Resources::new_relative_to_cache().unwrap();
+    /// ```
+    pub fn new_relative_to_cache() -> Result<Self> {
+        let cache_dir_path = get_cache_path()?;
+        Ok(Resources::new(cache_dir_path))
+    }
+
+    /// Creates a resource manager for the specified app in the cache directory.  An
+    /// error may be returned if the cache path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile app in the cache directory.
+    /// let resources = Resources::new_app_relative_to_cache("projectile").unwrap();
+    /// ```
+    pub fn new_app_relative_to_cache(app_name: impl AsRef<str>) -> Result<Self> {
+        Resources::new_dir_relative_to_cache(app_name.as_ref())
+    }
+
+    /// Creates a resource manager for the specified directory in the cache directory.
+    /// An error may be returned if the cache path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile dir in the cache directory.
+    /// let resources = Resources::new_dir_relative_to_cache("projectile").unwrap();
+    /// ```
+    pub fn new_dir_relative_to_cache(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut dir_path = get_cache_path()?;
+        dir_path.push(dir);
+        Ok(Resources::new(dir_path))
+    }
+
+    /// Creates a resource manager for the state directory.  An error may be returned if
+    /// the state path cannot be retrieved.  Only available on Linux.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the state directory.
+    /// let resources = Resources::new_relative_to_state().unwrap();
+    /// ```
+    pub fn new_relative_to_state() -> Result<Self> {
+        let state_dir_path = get_state_path()?;
+        Ok(Resources::new(state_dir_path))
+    }
+
+    /// Creates a resource manager for the specified app in the state directory.  An
+    /// error may be returned if the state path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile app in the state directory.
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    /// ```
+    pub fn new_app_relative_to_state(app_name: impl AsRef<str>) -> Result<Self> {
+        Resources::new_dir_relative_to_state(app_name.as_ref())
+    }
+
+    /// Creates a resource manager for the specified directory in the state directory.
+    /// An error may be returned if the state path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile dir in the state directory.
+    /// let resources = Resources::new_dir_relative_to_state("projectile").unwrap();
+    /// ```
+    pub fn new_dir_relative_to_state(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut dir_path = get_state_path()?;
+        dir_path.push(dir);
+        Ok(Resources::new(dir_path))
+    }
+
+    /// Creates a resource manager for the runtime directory.  An error may be returned if
+    /// the runtime path cannot be retrieved.  Only available on Linux
+    /// when `$XDG_RUNTIME_DIR` is set.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the runtime directory.
+    /// let resources = Resources::new_relative_to_runtime().unwrap();
+    /// ```
+    pub fn new_relative_to_runtime() -> Result<Self> {
+        let runtime_dir_path = get_runtime_path()?;
+        Ok(Resources::new(runtime_dir_path))
+    }
+
+    /// Creates a resource manager for the specified app in the runtime directory.  An
+    /// error may be returned if the runtime path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile app in the runtime directory.
+    /// let resources = Resources::new_app_relative_to_runtime("projectile").unwrap();
+    /// ```
+    pub fn new_app_relative_to_runtime(app_name: impl AsRef<str>) -> Result<Self> {
+        Resources::new_dir_relative_to_runtime(app_name.as_ref())
+    }
+
+    /// Creates a resource manager for the specified directory in the runtime directory.
+    /// An error may be returned if the runtime path cannot be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Create a new Resources for the projectile dir in the runtime directory.
+    /// let resources = Resources::new_dir_relative_to_runtime("projectile").unwrap();
+    /// ```
+    pub fn new_dir_relative_to_runtime(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut dir_path = get_runtime_path()?;
+        dir_path.push(dir);
+        Ok(Resources::new(dir_path))
+    }
+
+    /// Sets how files are written by this resource manager.  Defaults to
+    /// [`WriteMode::Atomic`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::{Resources, WriteMode};
+    ///
+    /// // Create a new Resources for the tmp directory that writes files in place.
+    /// let resources = Resources::new("/tmp").with_write_mode(WriteMode::Direct);
+    /// assert_eq!(resources.write_mode(), WriteMode::Direct);
+    /// ```
+    pub fn with_write_mode(mut self, write_mode: WriteMode) -> Self {
+        self.write_mode = write_mode;
+        self
+    }
+
+    /// Returns how files are written by this resource manager.
+    pub fn write_mode(&self) -> WriteMode {
+        self.write_mode
+    }
+
+    /// Sets whether relative paths are checked to stay inside the resources directory.  In
+    /// strict mode, paths are normalized and symlinks are resolved before every access, and
+    /// paths that end up outside of the directory are rejected with
+    /// [`AppResError::PathOutsideRoot`].  Defaults to `false`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{AppResError, Resources};
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile")
+    ///     .unwrap()
+    ///     .with_strict_paths(true);
+    ///
+    /// // Reading a file outside of the projectile directory fails.
+    /// let err = resources.load_from_file("../../.ssh/id_rsa").unwrap_err();
+    /// assert!(matches!(err, AppResError::PathOutsideRoot { .. }));
+    /// ```
+    pub fn with_strict_paths(mut self, strict_paths: bool) -> Self {
+        self.strict_paths = strict_paths;
+        self
+    }
+
+    /// Returns whether relative paths are checked to stay inside the resources directory.
+    pub fn strict_paths(&self) -> bool {
+        self.strict_paths
+    }
+
+    /// Reads files that are missing from the directory from files embedded into the binary
+    /// instead, so that the files on disk override the embedded ones.  Files are still
+    /// written to the directory.  See [`EmbeddedDir`] for how to embed a directory.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::EmbeddedDir;
+    /// use appres::Resources;
+    ///
+    /// static ASSETS: EmbeddedDir = EmbeddedDir::new(&[("theme.toml", b"bg = 0" as &[u8])]);
+    ///
+    /// // Read theme.toml from the assets folder if it is there, from the binary otherwise.
+    /// let resources = Resources::new_dir_relative_to_executable("assets")
+    ///     .unwrap()
+    ///     .with_embedded(&ASSETS);
+    /// let theme = resources.load_from_file("theme.toml").unwrap();
+    /// ```
+    pub fn with_embedded(mut self, embedded: &'static EmbeddedDir) -> Self {
+        self.backend = Arc::new(OverlayBackend::from_arcs(self.backend, Arc::new(*embedded)));
+        self
+    }
+
+    /// Reads and writes files through the backend instead of the file system.  Paths are
+    /// given to the backend relative to the resources directory.  See [`Backend`] for an
+    /// example.
+    pub fn with_backend(mut self, backend: impl Backend + 'static) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// Returns the backend that files are read from and written to.
+    pub fn backend(&self) -> &dyn Backend {
+        self.backend.as_ref()
+    }
+
     /// Loads a file at the path specified relative to the directory that was given when
     /// the resource manager was created. Returns a String or an error if the file could
     /// not be accessed for some reason.
@@ -216,15 +662,23 @@ impl Resources {
     /// let config_string = resources.load_from_file("config.toml").unwrap();
     /// ```
     pub fn load_from_file(&self, path: impl AsRef<Path>) -> Result<String> {
-        let mut file_path = self.path.clone();
-        file_path.push(path);
+        let path = path.as_ref();
+        let file_path = self.get_file_path(path);
+        let _lock = self.auto_lock(path, LockKind::Shared)?;
 
-        Ok(read_to_string(file_path)?)
+        let content = self
+            .backend
+            .read(&self.backend_path(path)?)
+            .context(&file_path, Operation::Read)?;
+        String::from_utf8(content)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+            .context(&file_path, Operation::Read)
     }
 
     /// Saves a file at the path specified relative to the directory that was given when
     /// the resource manager was created. An error may be returned if the file could not
-    /// be written to disk.
+    /// be written to disk.  The file is written atomically unless a different
+    /// [`WriteMode`] was set with [`Resources::with_write_mode`].
     ///
     /// For supported file types, enable the respective feature to serialize the data and
     /// then write to disk. For example, enable the `toml_resources` feature to access the
@@ -244,11 +698,37 @@ impl Resources {
     /// resources.save_to_file("username", username.as_bytes()).unwrap();
     /// ```
     pub fn save_to_file(&self, path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<()> {
-        let mut file_path = self.path.clone();
-        file_path.push(path);
+        let path = path.as_ref();
+        let backend_path = self.backend_path(path)?;
+        let _lock = self.auto_lock(path, LockKind::Exclusive)?;
+        self.back_up(&backend_path, content.as_ref())?;
+
+        self.backend
+            .write(&backend_path, content.as_ref(), self.write_mode)
+            .context(&self.get_file_path(path), Operation::Write)
+    }
+
+    /// Removes a file at the path specified relative to the directory that was given when
+    /// the resource manager was created.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Remove the username file in the assets folder
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// resources.remove_file("username").unwrap();
+    /// ```
+    pub fn remove_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let _lock = self.auto_lock(path, LockKind::Exclusive)?;
 
-        create_dir_all(&file_path.parent().ok_or(AppResError::NoParent)?)?;
-        Ok(write(file_path, content.as_ref())?)
+        self.backend
+            .remove(&self.backend_path(path)?)
+            .context(&self.get_file_path(path), Operation::Write)
     }
 
     /// Checks to see if the given path is a regular file that exists relative to the directory that
@@ -266,10 +746,8 @@ impl Resources {
     /// assert!(resources.has_file("config.toml"));
     /// ```
     pub fn has_file(&self, path: impl AsRef<Path>) -> bool {
-        let mut file_path = self.path.clone();
-        file_path.push(path);
-
-        file_path.is_file()
+        self.metadata(path.as_ref())
+            .is_ok_and(|metadata| metadata.kind == EntryKind::File)
     }
 
     /// Checks to see if the given path is a directory that exists relative to the directory that was
@@ -287,10 +765,8 @@ impl Resources {
     /// assert!(resources.has_dir("scripts"));
     /// ```
     pub fn has_dir(&self, path: impl AsRef<Path>) -> bool {
-        let mut file_path = self.path.clone();
-        file_path.push(path);
-
-        file_path.is_dir()
+        self.metadata(path.as_ref())
+            .is_ok_and(|metadata| metadata.kind == EntryKind::Dir)
     }
 
     /// Returns the full base path for the resource manager.
@@ -332,6 +808,58 @@ impl Resources {
         file_path.push(path);
         file_path
     }
+
+    /// Returns the full path for the given relative path, the same as
+    /// [`Resources::get_file_path`] unless strict paths are enabled with
+    /// [`Resources::with_strict_paths`].  In strict mode, the path is normalized and
+    /// [`AppResError::PathOutsideRoot`] is returned if it, or a symlink along it, leads
+    /// outside of the resources directory.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use appres::Resources;
+    ///
+    /// let resources = Resources::new("/nonexistent/assets").with_strict_paths(true);
+    /// assert_eq!(
+    ///     PathBuf::from("/nonexistent/assets/config.toml"),
+    ///     resources.resolve_file_path("scripts/../config.toml").unwrap()
+    /// );
+    /// assert!(resources.resolve_file_path("../config.toml").is_err());
+    /// assert!(resources.resolve_file_path("/etc/passwd").is_err());
+    /// ```
+    pub fn resolve_file_path(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        Ok(self.get_file_path(self.backend_path(path.as_ref())?))
+    }
+
+    /// Returns the path to hand to the backend for the relative path, checking that it
+    /// stays inside the resources directory first if strict paths are enabled.
+    pub(crate) fn backend_path(&self, path: &Path) -> Result<PathBuf> {
+        if !self.strict_paths {
+            return Ok(path.to_path_buf());
+        }
+
+        let outside = || AppResError::PathOutsideRoot {
+            path: path.to_path_buf(),
+            root: self.path.clone(),
+        };
+        let relative_path = sandbox::normalize(path).ok_or_else(outside)?;
+        if let Some(root) = self.backend.local_path(Path::new("")) {
+            if !sandbox::stays_inside(&root, &relative_path)? {
+                return Err(outside());
+            }
+        }
+        Ok(relative_path)
+    }
+
+    /// Returns the metadata of the file or directory at the relative path.
+    pub(crate) fn metadata(&self, path: &Path) -> Result<Metadata> {
+        self.backend.metadata(&self.backend_path(path)?)
+    }
 }
 
 /// Returns either the executable directory or [`AppResError::IOError`].
@@ -374,6 +902,196 @@ pub fn get_config_path() -> Result<PathBuf> {
     Option::ok_or(config_dir(), AppResError::ConfigDirNotFound)
 }
 
+/// Returns either the data directory or [`AppResError::DataDirNotFound`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use appres::get_data_path;
+///
+/// // Assume that the data directory is /home/nobody/.local/share
+/// let data_path = get_data_path().unwrap();
+/// assert_eq!(data_path, PathBuf::from("/home/nobody/.local/share"));
+/// ```
+pub fn get_data_path() -> Result<PathBuf> {
+    Option::ok_or(data_dir(), AppResError::DataDirNotFound)
+}
+
+/// Returns either the cache directory or [`AppResError::CacheDirNotFound`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use appres::get_cache_path;
+///
+/// // Assume that the cache directory is /home/nobody/.cache
+/// let cache_path = get_cache_path().unwrap();
+/// assert_eq!(cache_path, PathBuf::from("/home/nobody/.cache"));
+/// ```
+pub fn get_cache_path() -> Result<PathBuf> {
+    Option::ok_or(cache_dir(), AppResError::CacheDirNotFound)
+}
+
+/// Returns either the state directory or [`AppResError::StateDirNotFound`].  Only available
+/// on Linux.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use appres::get_state_path;
+///
+/// // Assume that the state directory is /home/nobody/.local/state
+/// let state_path = get_state_path().unwrap();
+/// assert_eq!(state_path, PathBuf::from("/home/nobody/.local/state"));
+/// ```
+pub fn get_state_path() -> Result<PathBuf> {
+    Option::ok_or(state_dir(), AppResError::StateDirNotFound)
+}
+
+/// Returns either the runtime directory or [`AppResError::RuntimeDirNotFound`].  Only
+/// available on Linux when `$XDG_RUNTIME_DIR` is set.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use appres::get_runtime_path;
+///
+/// // Assume that the runtime directory is /run/user/1000
+/// let runtime_path = get_runtime_path().unwrap();
+/// assert_eq!(runtime_path, PathBuf::from("/run/user/1000"));
+/// ```
+pub fn get_runtime_path() -> Result<PathBuf> {
+    Option::ok_or(runtime_dir(), AppResError::RuntimeDirNotFound)
+}
+
+/// Returns the directories that config files are searched in, from most to least important:
+/// the user's config directory followed by the system config directories
+/// (`$XDG_CONFIG_DIRS`, `/etc/xdg` if unset).
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use appres::get_config_search_paths;
+///
+/// // Assume that the config directory is /home/nobody/.config and $XDG_CONFIG_DIRS is unset
+/// let search_paths = get_config_search_paths();
+/// assert_eq!(
+///     search_paths,
+///     vec![PathBuf::from("/home/nobody/.config"), PathBuf::from("/etc/xdg")]
+/// );
+/// ```
+pub fn get_config_search_paths() -> Vec<PathBuf> {
+    search_paths(config_dir(), "XDG_CONFIG_DIRS", &["/etc/xdg"])
+}
+
+/// Returns the directories that data files are searched in, from most to least important:
+/// the user's data directory followed by the system data directories (`$XDG_DATA_DIRS`,
+/// `/usr/local/share` and `/usr/share` if unset).
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use appres::get_data_search_paths;
+///
+/// // Assume that the data directory is /home/nobody/.local/share and $XDG_DATA_DIRS is unset
+/// let search_paths = get_data_search_paths();
+/// assert_eq!(search_paths[1], PathBuf::from("/usr/local/share"));
+/// ```
+pub fn get_data_search_paths() -> Vec<PathBuf> {
+    search_paths(
+        data_dir(),
+        "XDG_DATA_DIRS",
+        &["/usr/local/share", "/usr/share"],
+    )
+}
+
+/// Returns the first existing file at `path` relative to the config search paths (see
+/// [`get_config_search_paths`]).
+/// Returns `None` for absolute paths and for paths that climb out of the search
+/// directories with `..`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::find_config_file;
+///
+/// // Find the projectile config, either the user's or the system-wide one
+/// let config_path = find_config_file("projectile/config.toml").unwrap();
+/// ```
+pub fn find_config_file(path: impl AsRef<Path>) -> Option<PathBuf> {
+    find_file(get_config_search_paths(), path.as_ref())
+}
+
+/// Returns the first existing file at `path` relative to the data search paths (see
+/// [`get_data_search_paths`]).
+/// Returns `None` for absolute paths and for paths that climb out of the search
+/// directories with `..`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::find_data_file;
+///
+/// // Find the projectile icon, either the user's or the system-wide one
+/// let icon_path = find_data_file("projectile/icon.png").unwrap();
+/// ```
+pub fn find_data_file(path: impl AsRef<Path>) -> Option<PathBuf> {
+    find_file(get_data_search_paths(), path.as_ref())
+}
+
+/// Returns the user directory followed by the absolute directories listed in the
+/// environment variable, or the defaults on platforms following the XDG specification.
+fn search_paths(user_dir: Option<PathBuf>, var: &str, defaults: &[&str]) -> Vec<PathBuf> {
+    let system_dirs: Vec<PathBuf> = match std::env::var_os(var) {
+        Some(dirs) if !dirs.is_empty() => std::env::split_paths(&dirs).collect(),
+        _ if cfg!(all(unix, not(target_os = "macos"))) => {
+            defaults.iter().map(PathBuf::from).collect()
+        }
+        _ => vec![],
+    };
+
+    user_dir
+        .into_iter()
+        .chain(system_dirs.into_iter().filter(|dir| dir.is_absolute()))
+        .collect()
+}
+
+fn find_file(search_paths: Vec<PathBuf>, path: &Path) -> Option<PathBuf> {
+    let path = sandbox::normalize(path)?;
+    search_paths
+        .into_iter()
+        .map(|dir| dir.join(&path))
+        .find(|file_path| file_path.is_file())
+}
+
 /// Expands `path` relative to the path of the executable. Returns either the expanded path or
 /// [`AppResError::IOError`].
 ///
@@ -432,10 +1150,29 @@ pub fn get_file_path_relative_to_config_path(path: impl AsRef<Path>) -> Result<P
 /// let content = read_from_file("config.toml").unwrap();
 /// ```
 pub fn read_from_file(path: impl AsRef<Path>) -> Result<String> {
-    Ok(read_to_string(path)?)
+    let path = path.as_ref();
+    read_to_string(path).context(path, Operation::Read)
 }
 
-/// Writes a slice to a file specified by the given path.
+/// Read the content of a file given its path as bytes.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::read_bytes_from_file;
+///
+/// // Read icon.png into a vector of bytes
+/// let content = read_bytes_from_file("icon.png").unwrap();
+/// ```
+pub fn read_bytes_from_file(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    std::fs::read(path).context(path, Operation::Read)
+}
+
+/// Writes a slice to a file specified by the given path.  The file is written atomically,
+/// use [`save_slice_to_file_with_mode`] to pick a different [`WriteMode`].
 ///
 /// # Examples
 ///
@@ -448,10 +1185,29 @@ pub fn read_from_file(path: impl AsRef<Path>) -> Result<String> {
 /// save_slice_to_file("config.toml", "Hello World".as_bytes()).unwrap();
 /// ```
 pub fn save_slice_to_file(path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<()> {
-    let path = path.as_ref();
+    save_slice_to_file_with_mode(path, content, WriteMode::Atomic)
+}
 
-    create_dir_all(path.parent().ok_or(AppResError::NoParent)?)?;
-    Ok(write(path, content.as_ref())?)
+/// Writes a slice to a file specified by the given path using the given [`WriteMode`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::{save_slice_to_file_with_mode, WriteMode};
+///
+/// // Write a slice of bytes to config.toml in place
+/// save_slice_to_file_with_mode("config.toml", "Hello World".as_bytes(), WriteMode::Direct)
+///     .unwrap();
+/// ```
+pub fn save_slice_to_file_with_mode(
+    path: impl AsRef<Path>,
+    content: impl AsRef<[u8]>,
+    write_mode: WriteMode,
+) -> Result<()> {
+    let path = path.as_ref();
+    atomic::write_file(path, content.as_ref(), write_mode).context(path, Operation::Write)
 }
 
 /// Writes a str to a file specified by the given path.
@@ -0,0 +1,271 @@
+use std::path::{Component, Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::atomic::is_temp_file_name;
+use crate::error::ResultExt;
+use crate::lock::is_lock_file_name;
+use crate::{Operation, Resources, Result};
+
+/// The kind of entries returned by [`Resources::list_dir_with`], [`Resources::walk_with`]
+/// and [`Resources::glob_with`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Regular files, and symlinks to them.
+    File,
+    /// Directories, and symlinks to them.
+    Dir,
+}
+
+/// Options for [`Resources::list_dir_with`], [`Resources::walk_with`] and
+/// [`Resources::glob_with`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ListOptions {
+    kind: Option<EntryKind>,
+    sorted: bool,
+}
+
+impl ListOptions {
+    /// Only returns entries of the given kind.  Defaults to returning every entry.
+    pub fn with_kind(mut self, kind: EntryKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Sets whether the entries are sorted by path.  Otherwise they are returned in the
+    /// order the file system lists them in, which can change from one run to the next.
+    /// Defaults to `false`.
+    pub fn with_sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    fn accepts(&self, resources: &Resources, path: &Path) -> bool {
+        match self.kind {
+            None => true,
+            Some(EntryKind::File) => resources.has_file(path),
+            Some(EntryKind::Dir) => resources.has_dir(path),
+        }
+    }
+
+    fn finish(&self, mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        if self.sorted {
+            paths.sort();
+        }
+        paths
+    }
+}
+
+impl Resources {
+    /// Lists the entries of a directory relative to the resources directory.  The returned
+    /// paths are relative to the resources directory, so they can be passed to the other
+    /// methods as they are.  The lock files and the temporary files of atomic writes that
+    /// this crate keeps next to files are left out, here and in [`Resources::walk`] and
+    /// [`Resources::glob`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use appres::Resources;
+    ///
+    /// // Assume that the assets folder has a plugins directory with a single file.
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let plugins = resources.list_dir("plugins").unwrap();
+    /// assert_eq!(plugins, vec![PathBuf::from("plugins/hello.toml")]);
+    /// ```
+    pub fn list_dir(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        self.list_dir_with(path, ListOptions::default())
+    }
+
+    /// Same as [`Resources::list_dir`], with control over filtering and sorting.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{EntryKind, ListOptions, Resources};
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // List the directories in the themes directory in order.
+    /// let options = ListOptions::default().with_kind(EntryKind::Dir).with_sorted(true);
+    /// let themes = resources.list_dir_with("themes", options).unwrap();
+    /// ```
+    pub fn list_dir_with(
+        &self,
+        path: impl AsRef<Path>,
+        options: ListOptions,
+    ) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for (relative_path, _) in self.read_dir_relative(path.as_ref())? {
+            if options.accepts(self, &relative_path) {
+                paths.push(relative_path);
+            }
+        }
+        Ok(options.finish(paths))
+    }
+
+    /// Lists the entries of a directory relative to the resources directory and of all of
+    /// its subdirectories.  Symlinks to directories are returned but not followed.  The
+    /// returned paths are relative to the resources directory.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // List everything in the assets folder.
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let everything = resources.walk("").unwrap();
+    /// ```
+    pub fn walk(&self, path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        self.walk_with(path, ListOptions::default())
+    }
+
+    /// Same as [`Resources::walk`], with control over filtering and sorting.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{EntryKind, ListOptions, Resources};
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // List every file below the scripts directory in order.
+    /// let options = ListOptions::default().with_kind(EntryKind::File).with_sorted(true);
+    /// let scripts = resources.walk_with("scripts", options).unwrap();
+    /// ```
+    pub fn walk_with(&self, path: impl AsRef<Path>, options: ListOptions) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        self.walk_into(path.as_ref(), &mut |relative_path| {
+            if options.accepts(self, &relative_path) {
+                paths.push(relative_path);
+            }
+        })?;
+        Ok(options.finish(paths))
+    }
+
+    /// Lists the paths relative to the resources directory that match a glob pattern.  `*`
+    /// and `?` match within a single path component, `[...]` matches a set of characters
+    /// and `**` matches any number of directories.  A pattern that does not match anything
+    /// returns an empty list.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Find every yaml file below the themes directory.
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let themes = resources.glob("themes/**/*.yaml").unwrap();
+    /// ```
+    pub fn glob(&self, pattern: impl AsRef<str>) -> Result<Vec<PathBuf>> {
+        self.glob_with(pattern, ListOptions::default())
+    }
+
+    /// Same as [`Resources::glob`], with control over filtering and sorting.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{EntryKind, ListOptions, Resources};
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Find the toml files in the plugins directory in order.
+    /// let options = ListOptions::default().with_kind(EntryKind::File).with_sorted(true);
+    /// let plugins = resources.glob_with("plugins/*.toml", options).unwrap();
+    /// ```
+    pub fn glob_with(
+        &self,
+        pattern: impl AsRef<str>,
+        options: ListOptions,
+    ) -> Result<Vec<PathBuf>> {
+        let pattern = pattern.as_ref();
+        let matcher = Pattern::new(pattern)?;
+        let match_options = MatchOptions {
+            require_literal_separator: true,
+            require_literal_leading_dot: true,
+            ..MatchOptions::new()
+        };
+
+        // Only walk the part of the tree that can match.
+        let base = literal_prefix(pattern);
+        if !self.has_dir(&base) {
+            return Ok(Vec::new());
+        }
+
+        let mut paths = Vec::new();
+        self.walk_into(&base, &mut |relative_path| {
+            if matcher.matches_path_with(&relative_path, match_options)
+                && options.accepts(self, &relative_path)
+            {
+                paths.push(relative_path);
+            }
+        })?;
+        Ok(options.finish(paths))
+    }
+
+    /// Calls `visit` with every entry below the relative directory, depth first.
+    fn walk_into(&self, path: &Path, visit: &mut dyn FnMut(PathBuf)) -> Result<()> {
+        for (relative_path, is_dir) in self.read_dir_relative(path)? {
+            if is_dir {
+                self.walk_into(&relative_path, visit)?;
+            }
+            visit(relative_path);
+        }
+        Ok(())
+    }
+
+    /// Reads a directory relative to the resources directory, leaving out lock and temporary
+    /// files.  Returns the relative paths of its entries along with whether they are
+    /// directories that can be walked into.
+    fn read_dir_relative(&self, path: &Path) -> Result<Vec<(PathBuf, bool)>> {
+        let dir_path = self.get_file_path(path);
+        let entries = self
+            .backend
+            .list(&self.backend_path(path)?)
+            .context(&dir_path, Operation::Read)?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| !is_lock_file_name(&entry.name) && !is_temp_file_name(&entry.name))
+            .map(|entry| {
+                let is_dir = entry.kind == EntryKind::Dir && !entry.is_symlink;
+                (path.join(entry.name), is_dir)
+            })
+            .collect())
+    }
+}
+
+/// Returns the leading components of a glob pattern that have no special characters.
+fn literal_prefix(pattern: &str) -> PathBuf {
+    let mut prefix = PathBuf::new();
+    let mut components = Path::new(pattern).components().peekable();
+    while let Some(component) = components.next() {
+        // The last component is matched against the entries, even if it is literal.
+        if components.peek().is_none() {
+            break;
+        }
+        match component {
+            Component::Normal(name) if !name.to_string_lossy().contains(&['*', '?', '['][..]) => {
+                prefix.push(name)
+            }
+            _ => break,
+        }
+    }
+    prefix
+}
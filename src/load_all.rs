@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::{AppResError, EntryKind, ListOptions, Resources, Result};
+
+/// The files of a directory loaded by [`Resources::load_all`].
+#[derive(Debug)]
+pub struct LoadedDir<T> {
+    /// The files that were loaded, keyed by file stem (`profiles/work.toml` is `work`).
+    pub values: BTreeMap<String, T>,
+    /// The files that could not be loaded, keyed by their path relative to the resources
+    /// directory.
+    pub errors: BTreeMap<PathBuf, AppResError>,
+}
+
+impl<T> LoadedDir<T> {
+    /// Returns the loaded files, or the first error if any of the files could not be
+    /// loaded.
+    pub fn into_result(self) -> Result<BTreeMap<String, T>> {
+        match self.errors.into_iter().next() {
+            Some((_, err)) => Err(err),
+            None => Ok(self.values),
+        }
+    }
+}
+
+impl Resources {
+    /// Read every file with a known format in a directory relative to the resources
+    /// directory and deserialize it.  The format of each file is picked from its extension
+    /// the same way as in [`Resources::load`], and files without a known format are skipped.
+    ///
+    /// A file that cannot be loaded does not stop the others from being loaded: its error is
+    /// collected in [`LoadedDir::errors`].  If several files have the same stem, only the first one
+    /// in alphabetical order is loaded and the others are reported as
+    /// [`AppResError::DuplicateStem`].  An error is only returned if the directory itself
+    /// cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Profile {
+    ///     name: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load every profile in the profiles directory, e.g. profiles/work.toml as "work".
+    /// let profiles = resources.load_all::<Profile>("profiles").unwrap();
+    /// for (path, err) in &profiles.errors {
+    ///     eprintln!("skipping {}: {}", path.display(), err);
+    /// }
+    /// let work = &profiles.values["work"];
+    /// ```
+    pub fn load_all<T>(&self, dir: impl AsRef<Path>) -> Result<LoadedDir<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let options = ListOptions::default()
+            .with_kind(EntryKind::File)
+            .with_sorted(true);
+
+        let mut loaded = LoadedDir {
+            values: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        };
+        let mut stems = BTreeSet::new();
+        for path in self.list_dir_with(dir, options)? {
+            if self.format_for(&path).is_err() {
+                continue;
+            }
+            let stem = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().into_owned(),
+                None => continue,
+            };
+
+            if !stems.insert(stem.clone()) {
+                loaded.errors.insert(path, AppResError::DuplicateStem(stem));
+                continue;
+            }
+            match self.load(&path) {
+                Ok(thing) => {
+                    loaded.values.insert(stem, thing);
+                }
+                Err(err) => {
+                    loaded.errors.insert(path, err);
+                }
+            }
+        }
+
+        Ok(loaded)
+    }
+}
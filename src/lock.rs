@@ -0,0 +1,326 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::{create_dir_all, File, OpenOptions, TryLockError};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::ResultExt;
+use crate::{AppResError, Operation, Resources, Result};
+
+/// Options for locking files, see [`Resources::with_locking`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockOptions {
+    timeout: Option<Duration>,
+    poll_interval: Duration,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(10)),
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+impl LockOptions {
+    /// Sets how long to wait for another process to release a lock before giving up with
+    /// [`AppResError::LockTimeout`].  Defaults to 10 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Waits for locks for as long as it takes.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Sets how often a lock is tried again while waiting with a timeout.  Defaults to 10
+    /// milliseconds.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+/// Whether other processes can hold the lock at the same time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LockKind {
+    Shared,
+    Exclusive,
+}
+
+/// An advisory lock on a file, released when dropped or unlocked.  Returned by
+/// [`Resources::lock_shared`] and [`Resources::lock_exclusive`].
+#[derive(Debug)]
+pub struct FileLock {
+    /// Files that are not on disk are not locked.
+    file: Option<File>,
+}
+
+impl FileLock {
+    /// Releases the lock.
+    pub fn unlock(self) {
+        // Closing the file releases the lock as well, should this fail.
+        if let Some(file) = &self.file {
+            let _ = file.unlock();
+        }
+    }
+
+    fn none() -> Self {
+        Self { file: None }
+    }
+}
+
+/// Returns the path of the lock file for a file, `.name.lock` next to it.  The file itself
+/// cannot be locked since atomic writes replace it.
+fn lock_file_path(path: &Path) -> Option<PathBuf> {
+    let mut name = OsString::from(".");
+    name.push(path.file_name()?);
+    name.push(".lock");
+    Some(path.with_file_name(name))
+}
+
+/// Returns whether the file name is that of a lock file, `.name.lock`.
+pub(crate) fn is_lock_file_name(name: &OsStr) -> bool {
+    name.to_str()
+        .and_then(|name| name.strip_prefix('.'))
+        .and_then(|name| name.strip_suffix(".lock"))
+        .is_some_and(|locked_name| !locked_name.is_empty())
+}
+
+/// Locks the file, waiting for the lock as the options say.
+fn acquire(file: &File, kind: LockKind, options: LockOptions) -> std::io::Result<bool> {
+    let timeout = match options.timeout {
+        Some(timeout) => timeout,
+        None => {
+            match kind {
+                LockKind::Shared => file.lock_shared()?,
+                LockKind::Exclusive => file.lock()?,
+            }
+            return Ok(true);
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let result = match kind {
+            LockKind::Shared => file.try_lock_shared(),
+            LockKind::Exclusive => file.try_lock(),
+        };
+        match result {
+            Ok(()) => return Ok(true),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(options.poll_interval)
+            }
+            Err(TryLockError::WouldBlock) => return Ok(false),
+            Err(TryLockError::Error(err)) => return Err(err),
+        }
+    }
+}
+
+impl Resources {
+    /// Locks files against other processes while they are loaded and saved, so that two
+    /// instances of an application do not read a file that the other one is in the middle
+    /// of replacing.  Loading a file takes a shared lock, saving or removing it takes an
+    /// exclusive one.  Readers opened with [`Resources::open_reader`] and files mapped
+    /// with `Resources::load_mmap` hold a shared lock until they are dropped, and writers
+    /// opened with [`Resources::open_writer`] hold an exclusive lock until they are
+    /// finished or dropped.  Locking is off by default.
+    ///
+    /// Methods that load a file, change it and save it again, such as `Resources::update`
+    /// and the `edit_*_file` methods of the format extension traits, hold an exclusive lock
+    /// from loading to saving, so that instances of an application that do the same do not
+    /// overwrite each other's changes.
+    ///
+    /// Locks are advisory: processes that do not lock the file are not stopped from
+    /// accessing it.  They are kept in a `.name.lock` file next to the file, which is left
+    /// behind.  Files that are not on disk are not locked.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use appres::{LockOptions, Resources};
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile")
+    ///     .unwrap()
+    ///     .with_locking(LockOptions::default().with_timeout(Duration::from_secs(1)));
+    ///
+    /// // Waits up to a second for other instances that are saving state.txt.
+    /// let state = resources.load_from_file("state.txt").unwrap();
+    /// ```
+    pub fn with_locking(mut self, options: LockOptions) -> Self {
+        self.locking = Some(options);
+        self
+    }
+
+    /// Returns how files are locked while they are loaded and saved, if they are.
+    pub fn locking(&self) -> Option<LockOptions> {
+        self.locking
+    }
+
+    /// Takes a shared lock on the file at the path specified relative to the directory that
+    /// was given when the resource manager was created, which other processes can hold at
+    /// the same time but which keeps them from taking an exclusive lock.  Waits as set with
+    /// [`Resources::with_locking`], or with the default [`LockOptions`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use std::io::Read;
+    ///
+    /// use appres::Resources;
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    ///
+    /// // Stream history.log without another instance replacing it halfway through.
+    /// let lock = resources.lock_shared("history.log").unwrap();
+    /// let mut history = String::new();
+    /// resources
+    ///     .open_reader("history.log")
+    ///     .unwrap()
+    ///     .read_to_string(&mut history)
+    ///     .unwrap();
+    /// lock.unlock();
+    /// ```
+    pub fn lock_shared(&self, path: impl AsRef<Path>) -> Result<FileLock> {
+        self.lock(
+            path.as_ref(),
+            LockKind::Shared,
+            self.locking.unwrap_or_default(),
+        )
+    }
+
+    /// Takes an exclusive lock on the file at the path specified relative to the directory
+    /// that was given when the resource manager was created, which no other process can
+    /// hold at the same time.  Waits as set with [`Resources::with_locking`], or with the
+    /// default [`LockOptions`].
+    ///
+    /// Only one lock can be held on a file at a time within a process as well, so the
+    /// resource manager must not lock the file again while the lock is held.
+    pub fn lock_exclusive(&self, path: impl AsRef<Path>) -> Result<FileLock> {
+        self.lock(
+            path.as_ref(),
+            LockKind::Exclusive,
+            self.locking.unwrap_or_default(),
+        )
+    }
+
+    /// Locks the file for a load or a save if locking is enabled.
+    pub(crate) fn auto_lock(&self, path: &Path, kind: LockKind) -> Result<Option<FileLock>> {
+        match self.locking {
+            Some(options) => self.lock(path, kind, options).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Locks the file, waiting for the lock as the options say.
+    pub(crate) fn lock(
+        &self,
+        path: &Path,
+        kind: LockKind,
+        options: LockOptions,
+    ) -> Result<FileLock> {
+        let local_path = match self.backend.local_path(&self.backend_path(path)?) {
+            Some(local_path) => local_path,
+            None => return Ok(FileLock::none()),
+        };
+        let lock_path = lock_file_path(&local_path)
+            .ok_or_else(|| Error::from(ErrorKind::InvalidInput))
+            .context(&local_path, Operation::Lock)?;
+
+        if let Some(parent) = lock_path.parent() {
+            match kind {
+                LockKind::Exclusive => {
+                    create_dir_all(parent).context(&lock_path, Operation::Lock)?;
+                }
+                // There is nothing to read in a directory that does not exist.
+                LockKind::Shared if !parent.is_dir() => return Ok(FileLock::none()),
+                LockKind::Shared => {}
+            }
+        }
+
+        let file = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+        {
+            Ok(file) => file,
+            // Nobody can write to a read only directory, so readers need no lock there.
+            Err(err) if kind == LockKind::Shared && err.kind() == ErrorKind::PermissionDenied => {
+                return Ok(FileLock::none())
+            }
+            Err(err) => return Err(err).context(&lock_path, Operation::Lock),
+        };
+
+        if !acquire(&file, kind, options).context(&lock_path, Operation::Lock)? {
+            return Err(AppResError::LockTimeout {
+                path: self.get_file_path(path),
+                timeout: options.timeout.unwrap_or_default(),
+            });
+        }
+        Ok(FileLock { file: Some(file) })
+    }
+}
+
+#[cfg(feature = "serde_resources")]
+impl Resources {
+    /// Returns a copy that does not lock files, for use while a lock is held already, as
+    /// locking again would wait for ourselves.
+    pub(crate) fn without_locking(&self) -> Self {
+        Self {
+            locking: None,
+            ..self.clone()
+        }
+    }
+
+    /// Loads a file, lets `modify` change its content and saves it again if it changed while
+    /// holding an exclusive lock on it, even if locking is not enabled with
+    /// [`Resources::with_locking`].  A file that does not exist yet starts out as the
+    /// default value.  The format is picked from the extension, the same as with
+    /// [`Resources::load`].  Returns what `modify` returns.
+    ///
+    /// Waits for the lock as set with [`Resources::with_locking`], or with the default
+    /// [`LockOptions`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    ///
+    /// // Count the launches of every instance of the application.
+    /// let launches = resources
+    ///     .with_locked("launches.json", |launches: &mut u64| {
+    ///         *launches += 1;
+    ///         *launches
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn with_locked<T, R, F>(&self, path: impl AsRef<Path>, modify: F) -> Result<R>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize + Default,
+        F: FnOnce(&mut T) -> R,
+    {
+        let path = path.as_ref();
+        let format = self.format_for(path)?.as_format();
+        let locking = self.locking.unwrap_or_default();
+        let (result, _) = self.modify_as(format, path, Some(locking), modify)?;
+        Ok(result)
+    }
+}
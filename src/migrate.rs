@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::error::ResultExt;
+use crate::{AppResError, Operation, Resources, Result, Value};
+
+type Migration = Box<dyn Fn(&mut Value) -> Result<()> + Send + Sync>;
+
+/// A chain of migrations that upgrade a file from older versions of its schema to the
+/// current one.  The version is stored as a number under a top-level key of the file
+/// (`version` by default), and files without it are treated as version 1.
+///
+/// Each migration upgrades the untyped [`Value`] tree of the file by exactly one version,
+/// so the current version is one more than the highest version that has a migration.  The
+/// version key is removed before the file is deserialized, so the type loaded does not need
+/// a field for it.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+///
+/// use appres::{Migrations, Resources};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Config {
+///     hosts: Vec<String>,
+///     timeout_secs: u64,
+/// }
+///
+/// let migrations = Migrations::new()
+///     // Version 2 turned the single host into a list of hosts.
+///     .with_migration(1, |config| {
+///         if let Some(host) = config.as_object_mut().and_then(|config| config.remove("host")) {
+///             config["hosts"] = serde_json::json!([host]);
+///         }
+///         Ok(())
+///     })
+///     // Version 3 added a timeout.
+///     .with_migration(2, |config| {
+///         config["timeout_secs"] = serde_json::json!(30);
+///         Ok(())
+///     })
+///     .with_write_back(true);
+///
+/// // Load config.toml, upgrading it to version 3 and saving the upgraded file.
+/// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+/// let config: Config = resources.load_versioned("config.toml", &migrations).unwrap();
+/// ```
+pub struct Migrations {
+    version_key: String,
+    migrations: BTreeMap<u64, Migration>,
+    write_back: bool,
+    backup: bool,
+}
+
+impl Default for Migrations {
+    fn default() -> Self {
+        Self {
+            version_key: String::from("version"),
+            migrations: BTreeMap::new(),
+            write_back: false,
+            backup: true,
+        }
+    }
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field("version_key", &self.version_key)
+            .field("versions", &self.migrations.keys().collect::<Vec<_>>())
+            .field("write_back", &self.write_back)
+            .field("backup", &self.backup)
+            .finish()
+    }
+}
+
+impl Migrations {
+    /// Creates an empty chain of migrations, with version 1 as the current version.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the top-level key that holds the version.  Defaults to `version`.
+    pub fn with_version_key(mut self, version_key: impl Into<String>) -> Self {
+        self.version_key = version_key.into();
+        self
+    }
+
+    /// Registers the migration that upgrades a file from `from_version` to
+    /// `from_version + 1`.
+    pub fn with_migration<F>(mut self, from_version: u64, migration: F) -> Self
+    where
+        F: Fn(&mut Value) -> Result<()> + Send + Sync + 'static,
+    {
+        self.migrations.insert(from_version, Box::new(migration));
+        self
+    }
+
+    /// Sets whether upgraded files are written back to disk.  The upgraded object is saved
+    /// the same way as with [`Resources::save_versioned`].  Defaults to `false`.
+    pub fn with_write_back(mut self, write_back: bool) -> Self {
+        self.write_back = write_back;
+        self
+    }
+
+    /// Sets whether the original file is kept as `<file>.v<version>.bak` before an upgraded
+    /// file is written back, whatever the [`BackupPolicy`](crate::BackupPolicy) set with
+    /// [`Resources::with_backups`] is.  Defaults to `true`.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use appres::{Migrations, Resources};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("appres-migrate-{}", std::process::id()));
+    /// let resources = Resources::new(&dir);
+    /// resources.save_to_file("settings.json", r#"{"host": "a"}"#).unwrap();
+    ///
+    /// // Upgrade settings.json from version 1 to version 3 and write it back.
+    /// let migrations = Migrations::new()
+    ///     .with_migration(1, |_| Ok(()))
+    ///     .with_migration(2, |_| Ok(()))
+    ///     .with_write_back(true);
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    /// let _: BTreeMap<String, String> =
+    ///     resources.load_versioned("settings.json", &migrations).unwrap();
+    ///
+    /// // The original file is still there.
+    /// let original = resources.load_from_file("settings.json.v1.bak").unwrap();
+    /// assert_eq!(original, r#"{"host": "a"}"#);
+    /// std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn with_backup(mut self, backup: bool) -> Self {
+        self.backup = backup;
+        self
+    }
+
+    /// Returns the version that files are upgraded to.
+    pub fn current_version(&self) -> u64 {
+        self.migrations
+            .keys()
+            .next_back()
+            .map_or(1, |version| version + 1)
+    }
+
+    /// Upgrades the value to the current version.  Returns the version the value had.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use appres::Migrations;
+    ///
+    /// let migrations = Migrations::new().with_migration(1, |config| {
+    ///     config["retries"] = serde_json::json!(3);
+    ///     Ok(())
+    /// });
+    ///
+    /// let mut config = serde_json::json!({"host": "localhost"});
+    /// assert_eq!(migrations.migrate(&mut config).unwrap(), 1);
+    /// assert_eq!(config, serde_json::json!({"host": "localhost", "retries": 3, "version": 2}));
+    /// ```
+    pub fn migrate(&self, value: &mut Value) -> Result<u64> {
+        let found = match value.get(&self.version_key) {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| AppResError::InvalidVersion(version.to_string()))?,
+        };
+        let supported = self.current_version();
+        if found > supported {
+            return Err(AppResError::UnsupportedVersion { found, supported });
+        }
+
+        for version in found..supported {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or(AppResError::MissingMigration(version))?;
+            migration(value)?;
+        }
+        self.stamp(value)?;
+
+        Ok(found)
+    }
+
+    /// Sets the version of the value to the current version.
+    fn stamp(&self, value: &mut Value) -> Result<()> {
+        let map = value.as_object_mut().ok_or(AppResError::RootNotAMap)?;
+        map.insert(self.version_key.clone(), self.current_version().into());
+        Ok(())
+    }
+}
+
+/// An object serialized as a map with the version in front of its own keys.
+#[derive(Serialize)]
+struct Stamped<'a, C: ?Sized> {
+    #[serde(flatten)]
+    version: BTreeMap<&'a str, u64>,
+    #[serde(flatten)]
+    thing: &'a C,
+}
+
+impl Resources {
+    /// Read a versioned file from the resources directory, upgrade it to the current version
+    /// with the migrations and deserialize it.  The format is picked from the extension the
+    /// same way as in [`Resources::load`].
+    ///
+    /// See [`Migrations`] for an example.
+    pub fn load_versioned<T>(&self, path: impl AsRef<Path>, migrations: &Migrations) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + serde::Serialize,
+    {
+        let path = path.as_ref();
+        let format = self.format_for(path)?;
+
+        let file_content = self.load_bytes(path)?;
+        let mut value = self.parse_value_as(format, path, &file_content)?;
+        let version = migrations
+            .migrate(&mut value)
+            .context(&self.get_file_path(path), Operation::Parse)?;
+
+        if let Some(map) = value.as_object_mut() {
+            map.remove(&migrations.version_key);
+        }
+        let thing = self.deserialize_value(path, value)?;
+
+        if migrations.write_back && version != migrations.current_version() {
+            if migrations.backup {
+                self.save_to_file(backup_path(path, version), &file_content)?;
+            }
+            self.save_versioned(path, &thing, migrations)?;
+        }
+        Ok(thing)
+    }
+
+    /// Serialize an object, stamp it with the current version of the migrations and write it
+    /// to a path relative from the resources directory.  The format is picked from the
+    /// extension the same way as in [`Resources::save`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use std::collections::BTreeMap;
+    ///
+    /// use appres::{Migrations, Resources};
+    ///
+    /// let migrations = Migrations::new().with_migration(1, |_| Ok(()));
+    ///
+    /// // Write config.yaml with `version: 2` in it.
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    /// let config: BTreeMap<String, String> = BTreeMap::new();
+    /// resources.save_versioned("config.yaml", &config, &migrations).unwrap();
+    /// ```
+    pub fn save_versioned<C>(
+        &self,
+        path: impl AsRef<Path>,
+        thing: &C,
+        migrations: &Migrations,
+    ) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let stamped = Stamped {
+            version: BTreeMap::from([(
+                migrations.version_key.as_str(),
+                migrations.current_version(),
+            )]),
+            thing,
+        };
+        self.save(path, &stamped)
+    }
+}
+
+/// Returns `<file>.v<version>.bak` next to the file.
+fn backup_path(path: &Path, version: u64) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".v{}.bak", version));
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::fs::File;
+use std::ops::Deref;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::error::ResultExt;
+use crate::lock::{FileLock, LockKind};
+use crate::{Operation, Resources, Result};
+
+/// The content of a file loaded by [`Resources::load_mmap`].  Dereferences to the bytes of
+/// the file.
+pub struct MappedFile {
+    content: Content,
+    /// Released when the mapping is dropped.
+    _lock: Option<FileLock>,
+}
+
+enum Content {
+    Mapped(Mmap),
+    /// Files that are not on disk cannot be mapped and are read in full instead.
+    Loaded(Vec<u8>),
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.content {
+            Content::Mapped(mmap) => mmap,
+            Content::Loaded(content) => content,
+        }
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for MappedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedFile")
+            .field("mapped", &matches!(self.content, Content::Mapped(_)))
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Resources {
+    /// Maps a file at the path specified relative to the directory that was given when the
+    /// resource manager was created into memory, so that large assets are paged in as they
+    /// are used instead of being read up front.  Files that are not on disk are read in
+    /// full instead.  If locking is enabled with [`Resources::with_locking`], a shared lock
+    /// is held on the file until the [`MappedFile`] is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified by this or another process while the
+    /// returned [`MappedFile`] is alive, as the changes show up in the mapped bytes and
+    /// reading past the new end of the file crashes the process.  Files saved by this crate
+    /// are replaced atomically by default, which leaves existing mappings alone, but not
+    /// with [`WriteMode::Direct`](crate::WriteMode::Direct), which truncates the file in
+    /// place.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    /// // Note you need to enable the mmap_resources feature in Cargo.toml
+    ///
+    /// // Map the world.bin file in the assets folder, which nothing writes to while the
+    /// // game runs
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let world = unsafe { resources.load_mmap("world.bin") }.unwrap();
+    /// let header = &world[..16];
+    /// ```
+    pub unsafe fn load_mmap(&self, path: impl AsRef<Path>) -> Result<MappedFile> {
+        let path = path.as_ref();
+        let backend_path = self.backend_path(path)?;
+
+        if let Some(file_path) = self.backend.local_path(&backend_path) {
+            let lock = self.auto_lock(path, LockKind::Shared)?;
+            if file_path.is_file() {
+                let file = File::open(&file_path).context(&file_path, Operation::Read)?;
+                // SAFETY: the caller guarantees that the file is not modified while it is
+                // mapped, which is the only way for the mapping to change under us.
+                let mmap = unsafe { Mmap::map(&file) }.context(&file_path, Operation::Read)?;
+                return Ok(MappedFile {
+                    content: Content::Mapped(mmap),
+                    _lock: lock,
+                });
+            }
+        }
+
+        Ok(MappedFile {
+            content: Content::Loaded(self.load_bytes(path)?),
+            _lock: None,
+        })
+    }
+}
@@ -1,25 +1,88 @@
-use std::fs::{create_dir_all, write};
 use std::path::Path;
+#[cfg(feature = "json_schema_resources")]
+use std::path::PathBuf;
 
-use crate::{AppResError, Resources, Result};
+#[cfg(feature = "json_schema_resources")]
+use super::json_schema;
+use super::ResourceType;
+use crate::error::ResultExt;
+#[cfg(feature = "tokio_resources")]
+use crate::AsyncResources;
+use crate::{
+    save_slice_to_file, EnvOverrides, Format, Operation, Resources, Result, Validate, Value,
+};
+#[cfg(feature = "json_schema_resources")]
+use crate::{AppResError, ValidationErrors};
 
 pub trait JsonResourcesExt {
     /// Read json file from resources directory and deserialize it.
     fn load_from_json_file<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
     where
         T: serde::de::DeserializeOwned;
+    /// Read json file from resources directory, deserialize it and check it with
+    /// [`Validate`].
+    fn load_from_json_file_validated<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate;
+    /// Read json file from resources directory, check it against the JSON Schema in
+    /// `schema_file` and deserialize it.
+    #[cfg(feature = "json_schema_resources")]
+    fn load_from_json_file_with_schema<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        schema_file: impl AsRef<Path>,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned;
+    /// Read json file from resources directory, apply the environment variable overrides and
+    /// deserialize it.
+    fn load_from_json_file_with_env<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned;
+    /// Read json file from resources directory and deserialize it, writing `T::default()` to
+    /// the file first if it does not exist yet.
+    fn load_or_init_json_file<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned;
+    /// Read json file from resources directory and deserialize it, writing `default` to the
+    /// file first if it does not exist yet.  If `fill_missing` is set, keys of `default`
+    /// that are missing from an existing file are added to it.
+    fn load_or_init_json_file_with<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned;
     /// Writes json file to a path relative from the resources directory.
-    fn save_to_json_file<C: ?Sized>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    fn save_to_json_file<C>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
     where
-        C: serde::Serialize;
+        C: serde::Serialize + ?Sized;
+    /// Read json file from resources directory, let `modify` change it and write it back if
+    /// it changed, starting from `T::default()` if the file does not exist yet.
+    fn update_json_file<T, F>(&self, json_file: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T);
     /// Writes json file to a path relative from the resources directory in a pretty format.
-    fn pretty_save_to_json_file<C: ?Sized>(
+    fn pretty_save_to_json_file<C>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized;
+    /// Writes json file to a path relative from the resources directory in a pretty format,
+    /// along with a JSON Schema for it that editors pick up.
+    #[cfg(feature = "json_schema_resources")]
+    fn save_to_json_file_with_schema<C>(
         &self,
         json_file: impl AsRef<Path>,
         thing: &C,
     ) -> Result<()>
     where
-        C: serde::Serialize;
+        C: serde::Serialize + schemars::JsonSchema;
 }
 
 impl JsonResourcesExt for Resources {
@@ -50,8 +113,217 @@ impl JsonResourcesExt for Resources {
     where
         T: serde::de::DeserializeOwned,
     {
-        let file_content = self.load_from_file(json_file)?;
-        Ok(serde_json::from_str(&file_content)?)
+        self.load_with(&JsonFormat, json_file)
+    }
+
+    /// Read json file from resources directory, deserialize it and check it with
+    /// [`Validate`], failing with [`AppResError::Validation`](crate::AppResError::Validation)
+    /// if any invariant is violated.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::{Resources, Validate, ValidationErrors};
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// impl Validate for Config {
+    ///     fn validate(&self, errors: &mut ValidationErrors) {
+    ///         if self.port < 1024 {
+    ///             errors.add("port", "must be at least 1024");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load, parse and validate the config.json file in the assets folder
+    /// let config: Config = resources.load_from_json_file_validated("config.json").unwrap();
+    /// ```
+    fn load_from_json_file_validated<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate,
+    {
+        let json_file = json_file.as_ref();
+        let thing = self.load_from_json_file(json_file)?;
+        self.validated(json_file, thing)
+    }
+
+    /// Read json file from resources directory, check it against the JSON Schema in
+    /// `schema_file` and deserialize it, failing with
+    /// [`AppResError::Validation`](crate::AppResError::Validation) listing every place where
+    /// the file does not match the schema.  See [`validate_json`] for the parts of JSON
+    /// Schema that are supported.  The `$schema` key that
+    /// [`JsonResourcesExt::save_to_json_file_with_schema`] adds is ignored.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the json_schema_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Check the config.json file against config.schema.json before parsing it
+    /// let config: Config = resources
+    ///     .load_from_json_file_with_schema("config.json", "config.schema.json")
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "json_schema_resources")]
+    fn load_from_json_file_with_schema<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        schema_file: impl AsRef<Path>,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let json_file = json_file.as_ref();
+        let schema: Value = self.load_with(&JsonFormat, schema_file)?;
+        let mut document: Value = self.load_with(&JsonFormat, json_file)?;
+
+        // The schema reference is there for editors, it is not part of the content.
+        if let Value::Object(object) = &mut document {
+            object.shift_remove(SCHEMA_KEY);
+        }
+        validate_json(&document, &schema)
+            .context(&self.get_file_path(json_file), Operation::Validate)?;
+        self.deserialize_value(json_file, document)
+    }
+
+    /// Read json file from resources directory, apply the environment variable overrides and
+    /// deserialize it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::{EnvOverrides, Resources};
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load and parse the config.json file in the assets folder, letting the
+    /// // ASSETS_STUFF environment variable override the stuff field
+    /// let env = EnvOverrides::new("ASSETS");
+    /// let config: Config = resources.load_from_json_file_with_env("config.json", &env).unwrap();
+    /// ```
+    fn load_from_json_file_with_env<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let json_file = json_file.as_ref();
+        let value: Value = self.load_with(&JsonFormat, json_file)?;
+        env.apply_and_deserialize(self, json_file, value)
+    }
+
+    /// Read json file from resources directory and deserialize it, writing `T::default()` to
+    /// the file first if it does not exist yet.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load and parse the config.json file, creating it on first run
+    /// let config: Config = resources.load_or_init_json_file("config.json").unwrap();
+    /// ```
+    fn load_or_init_json_file<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_json_file_with(json_file, T::default(), false)
+    }
+
+    /// Read json file from resources directory and deserialize it, writing `default` to the
+    /// file first if it does not exist yet.  If `fill_missing` is set, keys of `default`
+    /// that are missing from an existing file are added to it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load and parse the config.json file, creating it on first run and adding
+    /// // missing fields to it
+    /// let default = Config { stuff: String::from("Hello World") };
+    /// let config: Config = resources
+    ///     .load_or_init_json_file_with("config.json", default, true)
+    ///     .unwrap();
+    /// ```
+    fn load_or_init_json_file_with<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_as(
+            ResourceType::Json.erased(),
+            json_file.as_ref(),
+            default,
+            fill_missing,
+        )
     }
 
     /// Writes json file to a path relative from the resources directory.
@@ -78,12 +350,48 @@ impl JsonResourcesExt for Resources {
     /// let config = Config { stuff: String::from("Hello World") };
     /// resources.save_to_json_file("config.json", &config).unwrap();
     /// ```
-    fn save_to_json_file<C: ?Sized>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    fn save_to_json_file<C>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
     where
-        C: serde::Serialize,
+        C: serde::Serialize + ?Sized,
     {
-        let serialized_thing = serde_json::to_vec(&thing)?;
-        self.save_to_file(json_file, serialized_thing)
+        self.save_with(&JsonFormat, json_file, thing)
+    }
+
+    /// Read json file from resources directory, let `modify` change it and write it back if
+    /// it changed, starting from `T::default()` if the file does not exist yet.  Returns
+    /// whether the file was written.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the json_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct State {
+    ///     recent: Vec<String>,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    ///
+    /// // Remember a recently opened project in the state.json file
+    /// resources
+    ///     .update_json_file("state.json", |state: &mut State| {
+    ///         state.recent.push(String::from("rocket"));
+    ///     })
+    ///     .unwrap();
+    /// ```
+    fn update_json_file<T, F>(&self, json_file: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        self.update_as(&JsonFormat, json_file.as_ref(), modify)
     }
 
     /// Writes json file to a path relative from the resources directory in a pretty format.
@@ -110,16 +418,337 @@ impl JsonResourcesExt for Resources {
     /// let config = Config { stuff: String::from("Hello World") };
     /// resources.pretty_save_to_json_file("config.json", &config).unwrap();
     /// ```
-    fn pretty_save_to_json_file<C: ?Sized>(
+    fn pretty_save_to_json_file<C>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        self.save_with(&PrettyJsonFormat, json_file, thing)
+    }
+
+    /// Writes json file to a path relative from the resources directory in a pretty format,
+    /// along with a JSON Schema generated from the type of the object, so that editors
+    /// offer completion for the keys of the file.  The schema of `config.json` is written to
+    /// `config.schema.json`, and objects get a `$schema` key pointing to it.
+    ///
+    /// The `$schema` key is part of the file, so types with
+    /// `#[serde(deny_unknown_fields)]` must be loaded with
+    /// [`JsonResourcesExt::load_from_json_file_with_schema`], which drops it, rather than
+    /// with [`JsonResourcesExt::load_from_json_file`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use schemars::JsonSchema;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the json_schema_resources feature in Cargo.toml
+    /// use appres::json::JsonResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize, JsonSchema)]
+    /// struct Config {
+    ///     /// Port to listen on.
+    ///     port: u16,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Write config.json and config.schema.json
+    /// let config = Config { port: 8080 };
+    /// resources.save_to_json_file_with_schema("config.json", &config).unwrap();
+    /// ```
+    #[cfg(feature = "json_schema_resources")]
+    fn save_to_json_file_with_schema<C>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + schemars::JsonSchema,
+    {
+        for (path, content) in self.schema_files_for(json_file.as_ref(), thing)? {
+            self.save_to_file(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio_resources")]
+impl AsyncResources {
+    /// Async version of [`JsonResourcesExt::load_from_json_file`].
+    pub async fn load_from_json_file<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_json_file(json_file))
+            .await
+    }
+
+    /// Async version of [`JsonResourcesExt::load_from_json_file_validated`].
+    pub async fn load_from_json_file_validated<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_json_file_validated(json_file))
+            .await
+    }
+
+    /// Async version of [`JsonResourcesExt::load_from_json_file_with_schema`].
+    #[cfg(feature = "json_schema_resources")]
+    pub async fn load_from_json_file_with_schema<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        schema_file: impl AsRef<Path>,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        let schema_file = schema_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_json_file_with_schema(json_file, schema_file))
+            .await
+    }
+
+    /// Async version of [`JsonResourcesExt::load_from_json_file_with_env`].
+    pub async fn load_from_json_file_with_env<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        let env = env.clone();
+        self.run(move |resources| resources.load_from_json_file_with_env(json_file, &env))
+            .await
+    }
+
+    /// Async version of [`JsonResourcesExt::load_or_init_json_file`].
+    pub async fn load_or_init_json_file<T>(&self, json_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_or_init_json_file(json_file))
+            .await
+    }
+
+    /// Async version of [`JsonResourcesExt::load_or_init_json_file_with`].
+    pub async fn load_or_init_json_file_with<T>(
+        &self,
+        json_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        self.run(move |resources| {
+            resources.load_or_init_json_file_with(json_file, default, fill_missing)
+        })
+        .await
+    }
+
+    /// Async version of [`JsonResourcesExt::save_to_json_file`].  The object is serialized
+    /// before the file is written on the blocking thread pool.
+    pub async fn save_to_json_file<C>(&self, json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let json_file = json_file.as_ref();
+        let content = self
+            .resources()
+            .serialize_with(&JsonFormat, json_file, thing)?;
+        self.save_to_file(json_file, content).await
+    }
+
+    /// Async version of [`JsonResourcesExt::update_json_file`].
+    pub async fn update_json_file<T, F>(
+        &self,
+        json_file: impl AsRef<Path>,
+        modify: F,
+    ) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T) + Send + 'static,
+    {
+        let json_file = json_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.update_json_file(json_file, modify))
+            .await
+    }
+
+    /// Async version of [`JsonResourcesExt::pretty_save_to_json_file`].  The object is
+    /// serialized before the file is written on the blocking thread pool.
+    pub async fn pretty_save_to_json_file<C>(
+        &self,
+        json_file: impl AsRef<Path>,
+        thing: &C,
+    ) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let json_file = json_file.as_ref();
+        let content = self
+            .resources()
+            .serialize_with(&PrettyJsonFormat, json_file, thing)?;
+        self.save_to_file(json_file, content).await
+    }
+
+    /// Async version of [`JsonResourcesExt::save_to_json_file_with_schema`].  The object
+    /// and the schema are serialized before the files are written on the blocking thread
+    /// pool.
+    #[cfg(feature = "json_schema_resources")]
+    pub async fn save_to_json_file_with_schema<C>(
         &self,
         json_file: impl AsRef<Path>,
         thing: &C,
     ) -> Result<()>
     where
-        C: serde::Serialize,
+        C: serde::Serialize + schemars::JsonSchema,
+    {
+        for (path, content) in self
+            .resources()
+            .schema_files_for(json_file.as_ref(), thing)?
+        {
+            self.save_to_file(path, content).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The key of a json object that points editors to its schema.
+#[cfg(feature = "json_schema_resources")]
+const SCHEMA_KEY: &str = "$schema";
+
+#[cfg(feature = "json_schema_resources")]
+impl Resources {
+    /// Serializes the schema file and the json file written by
+    /// [`JsonResourcesExt::save_to_json_file_with_schema`], in the order they are written.
+    pub(crate) fn schema_files_for<C>(
+        &self,
+        json_file: &Path,
+        thing: &C,
+    ) -> Result<[(PathBuf, Vec<u8>); 2]>
+    where
+        C: serde::Serialize + schemars::JsonSchema,
+    {
+        let mut schema_name = json_file.file_stem().unwrap_or_default().to_os_string();
+        schema_name.push(".schema.json");
+        let schema_file = json_file.with_file_name(&schema_name);
+        let schema =
+            self.serialize_with(&PrettyJsonFormat, &schema_file, &json_schema_for::<C>()?)?;
+
+        let mut document = self.serialize_value(json_file, thing)?;
+        if let Value::Object(object) = document {
+            // Put the schema reference first, where people look for it.
+            let mut with_schema = serde_json::Map::new();
+            let reference = format!("./{}", schema_name.to_string_lossy());
+            with_schema.insert(SCHEMA_KEY.to_string(), Value::String(reference));
+            with_schema.extend(object);
+            document = Value::Object(with_schema);
+        }
+        let document = self.serialize_with(&PrettyJsonFormat, json_file, &document)?;
+
+        Ok([(schema_file, schema), (json_file.to_path_buf(), document)])
+    }
+}
+
+/// The json [`Format`], writing compact json.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::Resources;
+/// // Note you need to enable the json_resources feature in Cargo.toml
+/// use appres::json::JsonFormat;
+///
+/// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+///
+/// // Load and parse the list.json file in the assets folder
+/// let list: Vec<String> = resources.load_with(&JsonFormat, "list.json").unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonFormat;
+
+impl Format for JsonFormat {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+
+    fn serialize(&self, thing: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(thing)?)
+    }
+
+    fn deserialize_with(
+        &self,
+        content: &[u8],
+        visit: &mut dyn FnMut(&mut dyn erased_serde::Deserializer<'_>) -> Result<()>,
+    ) -> Result<()> {
+        let mut deserializer = serde_json::Deserializer::from_slice(content);
+        visit(&mut <dyn erased_serde::Deserializer>::erase(
+            &mut deserializer,
+        ))?;
+        Ok(deserializer.end()?)
+    }
+}
+
+impl JsonFormat {
+    /// Deserialize an object from bytes, reporting where in them parsing failed.
+    pub(crate) fn deserialize<T>(&self, content: &[u8]) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
     {
-        let serialized_thing = serde_json::to_vec_pretty(&thing)?;
-        self.save_to_file(json_file, serialized_thing)
+        Ok(serde_json::from_slice(content)?)
+    }
+}
+
+/// The json [`Format`], writing json in a pretty format.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::Resources;
+/// // Note you need to enable the json_resources feature in Cargo.toml
+/// use appres::json::PrettyJsonFormat;
+///
+/// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+///
+/// // Write the list to the list.json file in the assets folder (in pretty json format)
+/// let list = vec!["a", "b", "c"];
+/// resources.save_with(&PrettyJsonFormat, "list.json", &list).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrettyJsonFormat;
+
+impl Format for PrettyJsonFormat {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["json"]
+    }
+
+    fn serialize(&self, thing: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(thing)?)
+    }
+
+    fn deserialize_with(
+        &self,
+        content: &[u8],
+        visit: &mut dyn FnMut(&mut dyn erased_serde::Deserializer<'_>) -> Result<()>,
+    ) -> Result<()> {
+        JsonFormat.deserialize_with(content, visit)
     }
 }
 
@@ -147,7 +776,7 @@ pub fn load_json_from_slice<T>(json_content: impl AsRef<[u8]>) -> Result<T>
 where
     T: serde::de::DeserializeOwned,
 {
-    Ok(serde_json::from_slice(json_content.as_ref())?)
+    JsonFormat.deserialize(json_content.as_ref())
 }
 
 /// Deserialize a string in json format.
@@ -177,6 +806,89 @@ where
     Ok(serde_json::from_str(json_content.as_ref())?)
 }
 
+/// Check a json document against a JSON Schema, failing with
+/// [`AppResError::Validation`](crate::AppResError::Validation) listing every place where it
+/// does not match.
+///
+/// The commonly used keywords of JSON Schema are supported: `type`, `enum`, `const`,
+/// `properties`, `patternProperties`, `required`, `additionalProperties`, `items`,
+/// `pattern`, the `min*` and `max*` keywords, `exclusiveMinimum`, `exclusiveMaximum`,
+/// `multipleOf`, `uniqueItems`, `allOf`, `anyOf`, `oneOf`, `not` and `$ref` within the
+/// schema.  Other keywords, such as `format`, are ignored.  Patterns are checked with the
+/// [`regex`](https://docs.rs/regex) crate, which does not support lookaround or
+/// backreferences, so such patterns are reported as violations themselves.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use serde_json::json;
+///
+/// // Note that you need to enable the json_schema_resources feature in Cargo.toml
+/// use appres::json::validate_json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": { "port": { "type": "integer", "minimum": 1024 } },
+///     "required": ["port"],
+///     "additionalProperties": false
+/// });
+///
+/// assert!(validate_json(&json!({ "port": 8080 }), &schema).is_ok());
+/// let err = validate_json(&json!({ "port": 80, "prot": 8080 }), &schema).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid content: port: must be at least 1024; prot: is not an allowed key"
+/// );
+/// ```
+#[cfg(feature = "json_schema_resources")]
+pub fn validate_json(document: &Value, schema: &Value) -> Result<()> {
+    let mut errors = ValidationErrors::new();
+    json_schema::validate(schema, document, &mut errors);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(AppResError::Validation(errors))
+}
+
+/// Generate a JSON Schema for a type.  Objects are allowed a `$schema` key, so that files
+/// can point editors to their schema.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// use schemars::JsonSchema;
+///
+/// // Note that you need to enable the json_schema_resources feature in Cargo.toml
+/// use appres::json::json_schema_for;
+///
+/// #[derive(JsonSchema)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// let schema = json_schema_for::<Config>().unwrap();
+/// assert_eq!(schema["properties"]["port"]["type"], "integer");
+/// ```
+#[cfg(feature = "json_schema_resources")]
+pub fn json_schema_for<T>() -> Result<Value>
+where
+    T: schemars::JsonSchema + ?Sized,
+{
+    let schema = schemars::gen::SchemaGenerator::default().into_root_schema_for::<T>();
+    let mut schema = serde_json::to_value(schema)?;
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        properties.insert(
+            SCHEMA_KEY.to_string(),
+            serde_json::json!({ "type": "string" }),
+        );
+    }
+    Ok(schema)
+}
+
 /// Serialize an object into json format and write it to a file as specified by the given path.
 ///
 /// # Examples
@@ -198,15 +910,15 @@ where
 /// let config = Config { stuff: String::from("Hello World") };
 /// save_to_json_file("config.json", &config).unwrap();
 /// ```
-pub fn save_to_json_file<C: ?Sized>(json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+pub fn save_to_json_file<C>(json_file: impl AsRef<Path>, thing: &C) -> Result<()>
 where
-    C: serde::Serialize,
+    C: serde::Serialize + ?Sized,
 {
-    let serialized_thing = serde_json::to_vec(&thing)?;
-
-    let json_file_dir = json_file.as_ref().parent().ok_or(AppResError::NoParent)?;
-    create_dir_all(json_file_dir)?;
-    Ok(write(json_file, serialized_thing)?)
+    let json_file = json_file.as_ref();
+    let serialized_thing = JsonFormat
+        .serialize(&thing)
+        .context(json_file, Operation::Serialize)?;
+    save_slice_to_file(json_file, serialized_thing)
 }
 
 /// Serialize an object into json pretty format and write it to a file as specified by the given
@@ -231,13 +943,13 @@ where
 /// let config = Config { stuff: String::from("Hello World") };
 /// pretty_save_to_json_file("config.json", &config).unwrap();
 /// ```
-pub fn pretty_save_to_json_file<C: ?Sized>(json_file: impl AsRef<Path>, thing: &C) -> Result<()>
+pub fn pretty_save_to_json_file<C>(json_file: impl AsRef<Path>, thing: &C) -> Result<()>
 where
-    C: serde::Serialize,
+    C: serde::Serialize + ?Sized,
 {
-    let serialized_thing = serde_json::to_vec_pretty(&thing)?;
-
-    let json_file_dir = json_file.as_ref().parent().ok_or(AppResError::NoParent)?;
-    create_dir_all(json_file_dir)?;
-    Ok(write(json_file, serialized_thing)?)
+    let json_file = json_file.as_ref();
+    let serialized_thing = PrettyJsonFormat
+        .serialize(&thing)
+        .context(json_file, Operation::Serialize)?;
+    save_slice_to_file(json_file, serialized_thing)
 }
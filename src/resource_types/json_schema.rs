@@ -0,0 +1,417 @@
+//! A validator for the commonly used subset of JSON Schema.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+
+use crate::ValidationErrors;
+
+/// Checks a document against a schema, adding every violation to `errors`.
+pub(crate) fn validate(schema: &Value, document: &Value, errors: &mut ValidationErrors) {
+    let validator = Validator {
+        root: schema,
+        following: RefCell::new(Vec::new()),
+        patterns: RefCell::new(HashMap::new()),
+    };
+    validator.check(schema, document, "", errors);
+}
+
+struct Validator<'a> {
+    /// The whole schema, that `$ref`s point into.
+    root: &'a Value,
+    /// The `$ref`s being followed, along with the value they are followed for, so that
+    /// schemas referring to themselves are reported instead of followed forever.
+    following: RefCell<Vec<(*const Value, String)>>,
+    /// The compiled `pattern` and `patternProperties` regular expressions, by pattern.
+    patterns: RefCell<HashMap<String, Option<Regex>>>,
+}
+
+impl Validator<'_> {
+    fn check(&self, schema: &Value, value: &Value, path: &str, errors: &mut ValidationErrors) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return errors.add(path, "is not allowed"),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            self.check_reference(reference, value, path, errors);
+        }
+
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+                // The other keywords would only repeat that the value is of the wrong type.
+                return errors.add(
+                    path,
+                    format!("expected {}, found {}", types.join(" or "), type_of(value)),
+                );
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                errors.add(path, format!("must be one of {}", allowed.join(", ")));
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                errors.add(path, format!("must be {}", constant));
+            }
+        }
+
+        self.check_combinators(schema, value, path, errors);
+        match value {
+            Value::Object(object) => self.check_object(schema, object, path, errors),
+            Value::Array(array) => self.check_array(schema, array, path, errors),
+            Value::String(string) => self.check_string(schema, string, path, errors),
+            Value::Number(number) => check_number(schema, number, path, errors),
+            _ => {}
+        }
+    }
+
+    fn check_reference(
+        &self,
+        reference: &str,
+        value: &Value,
+        path: &str,
+        errors: &mut ValidationErrors,
+    ) {
+        let target = match reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.root.pointer(pointer))
+        {
+            Some(target) => target,
+            None => return errors.add(path, format!("cannot resolve the schema {}", reference)),
+        };
+
+        let followed = (value as *const Value, reference.to_string());
+        if self.following.borrow().contains(&followed) {
+            return errors.add(path, format!("the schema {} refers to itself", reference));
+        }
+        self.following.borrow_mut().push(followed);
+        self.check(target, value, path, errors);
+        self.following.borrow_mut().pop();
+    }
+
+    fn check_combinators(
+        &self,
+        schema: &Map<String, Value>,
+        value: &Value,
+        path: &str,
+        errors: &mut ValidationErrors,
+    ) {
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            for subschema in all_of {
+                self.check(subschema, value, path, errors);
+            }
+        }
+        if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any_of
+                .iter()
+                .any(|subschema| self.matches(subschema, value))
+            {
+                errors.add(path, "does not match any of the allowed schemas");
+            }
+        }
+        if let Some(one_of) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = one_of
+                .iter()
+                .filter(|subschema| self.matches(subschema, value))
+                .count();
+            if matching != 1 {
+                errors.add(
+                    path,
+                    format!("must match exactly one schema, matches {}", matching),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.matches(not, value) {
+                errors.add(path, "matches a schema that is not allowed");
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        errors: &mut ValidationErrors,
+    ) {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    errors.add(field_path(path, key), "is required");
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let mut pattern_properties = Vec::new();
+        if let Some(patterns) = schema.get("patternProperties").and_then(Value::as_object) {
+            for (pattern, subschema) in patterns {
+                match self.compile(pattern) {
+                    Some(regex) => pattern_properties.push((regex, subschema)),
+                    None => errors.add(path, format!("cannot check the pattern {}", pattern)),
+                }
+            }
+        }
+        let additional = schema.get("additionalProperties");
+
+        for (key, value) in object {
+            let field = field_path(path, key);
+            let mut matched = false;
+            if let Some(subschema) = properties.and_then(|properties| properties.get(key)) {
+                self.check(subschema, value, &field, errors);
+                matched = true;
+            }
+            for (regex, subschema) in &pattern_properties {
+                if regex.is_match(key) {
+                    self.check(subschema, value, &field, errors);
+                    matched = true;
+                }
+            }
+            if matched {
+                continue;
+            }
+
+            match additional {
+                Some(Value::Bool(false)) => errors.add(field, "is not an allowed key"),
+                Some(subschema) => self.check(subschema, value, &field, errors),
+                None => {}
+            }
+        }
+
+        check_count(
+            schema,
+            "minProperties",
+            "maxProperties",
+            object.len(),
+            "keys",
+            path,
+            errors,
+        );
+    }
+
+    fn check_array(
+        &self,
+        schema: &Map<String, Value>,
+        array: &[Value],
+        path: &str,
+        errors: &mut ValidationErrors,
+    ) {
+        match schema.get("items") {
+            Some(Value::Array(items)) => {
+                for (index, (subschema, value)) in items.iter().zip(array).enumerate() {
+                    self.check(subschema, value, &index_path(path, index), errors);
+                }
+            }
+            Some(items) => {
+                for (index, value) in array.iter().enumerate() {
+                    self.check(items, value, &index_path(path, index), errors);
+                }
+            }
+            None => {}
+        }
+
+        check_count(
+            schema,
+            "minItems",
+            "maxItems",
+            array.len(),
+            "items",
+            path,
+            errors,
+        );
+        let unique = schema.get("uniqueItems").and_then(Value::as_bool);
+        if unique == Some(true) {
+            let has_duplicates = array
+                .iter()
+                .enumerate()
+                .any(|(index, value)| array[..index].contains(value));
+            if has_duplicates {
+                errors.add(path, "must not contain duplicate items");
+            }
+        }
+    }
+
+    /// Checks whether the value matches the schema, without reporting why not.
+    fn matches(&self, schema: &Value, value: &Value) -> bool {
+        let mut errors = ValidationErrors::new();
+        self.check(schema, value, "", &mut errors);
+        errors.is_empty()
+    }
+
+    fn check_string(
+        &self,
+        schema: &Map<String, Value>,
+        string: &str,
+        path: &str,
+        errors: &mut ValidationErrors,
+    ) {
+        let length = string.chars().count();
+        check_count(
+            schema,
+            "minLength",
+            "maxLength",
+            length,
+            "characters",
+            path,
+            errors,
+        );
+
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match self.compile(pattern) {
+                Some(regex) if !regex.is_match(string) => {
+                    errors.add(path, format!("must match the pattern {}", pattern))
+                }
+                Some(_) => {}
+                None => errors.add(path, format!("cannot check the pattern {}", pattern)),
+            }
+        }
+    }
+
+    /// Compiles a `pattern` or `patternProperties` regular expression, once per pattern.
+    /// Like in JSON Schema, patterns are not anchored.
+    fn compile(&self, pattern: &str) -> Option<Regex> {
+        if let Some(regex) = self.patterns.borrow().get(pattern) {
+            return regex.clone();
+        }
+        let regex = Regex::new(pattern).ok();
+        self.patterns
+            .borrow_mut()
+            .insert(pattern.to_string(), regex.clone());
+        regex
+    }
+}
+
+fn check_number(
+    schema: &Map<String, Value>,
+    number: &Number,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    if let Some(divisor) = schema.get("multipleOf").and_then(Value::as_number) {
+        if !is_multiple_of(number, divisor) {
+            errors.add(path, format!("must be a multiple of {}", divisor));
+        }
+    }
+
+    let number = match number.as_f64() {
+        Some(number) => number,
+        None => return,
+    };
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+
+    if let Some(minimum) = bound("minimum") {
+        if number < minimum {
+            errors.add(path, format!("must be at least {}", minimum));
+        }
+    }
+    if let Some(maximum) = bound("maximum") {
+        if number > maximum {
+            errors.add(path, format!("must be at most {}", maximum));
+        }
+    }
+    if let Some(minimum) = bound("exclusiveMinimum") {
+        if number <= minimum {
+            errors.add(path, format!("must be greater than {}", minimum));
+        }
+    }
+    if let Some(maximum) = bound("exclusiveMaximum") {
+        if number >= maximum {
+            errors.add(path, format!("must be less than {}", maximum));
+        }
+    }
+}
+
+/// Checks `multipleOf`, exactly when both numbers are integers and allowing for rounding
+/// errors otherwise, so that `0.3` counts as a multiple of `0.1`.
+fn is_multiple_of(number: &Number, divisor: &Number) -> bool {
+    let as_integer = |number: &Number| {
+        number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from))
+    };
+    if let (Some(number), Some(divisor)) = (as_integer(number), as_integer(divisor)) {
+        return divisor <= 0 || number % divisor == 0;
+    }
+
+    let (number, divisor) = match (number.as_f64(), divisor.as_f64()) {
+        (Some(number), Some(divisor)) if divisor > 0.0 => (number, divisor),
+        _ => return true,
+    };
+    // Quotients too large to hold a fractional part are multiples as far as floats can tell.
+    let quotient = number / divisor;
+    !quotient.is_finite() || (quotient - quotient.round()).abs() <= 1e-9 * quotient.abs().max(1.0)
+}
+
+/// Checks a `min*` and `max*` keyword pair against the number of things in a value.
+fn check_count(
+    schema: &Map<String, Value>,
+    min_keyword: &str,
+    max_keyword: &str,
+    count: usize,
+    things: &str,
+    path: &str,
+    errors: &mut ValidationErrors,
+) {
+    let count = count as u64;
+    if let Some(min) = schema.get(min_keyword).and_then(Value::as_u64) {
+        if count < min {
+            errors.add(path, format!("must have at least {} {}", min, things));
+        }
+    }
+    if let Some(max) = schema.get(max_keyword).and_then(Value::as_u64) {
+        if count > max {
+            errors.add(path, format!("must have at most {} {}", max, things));
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => match value {
+            Value::Number(number) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().is_some_and(|number| number.fract() == 0.0)
+            }
+            _ => false,
+        },
+        name => type_of(value) == name,
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn field_path(path: &str, key: &str) -> String {
+    match path {
+        "" => key.to_string(),
+        path => format!("{}.{}", path, key),
+    }
+}
+
+fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
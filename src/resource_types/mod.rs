@@ -1,6 +1,309 @@
 #[cfg(feature = "json_resources")]
 pub mod json;
+#[cfg(feature = "json_schema_resources")]
+mod json_schema;
 #[cfg(feature = "toml_resources")]
 pub mod toml;
+#[cfg(feature = "toml_edit_resources")]
+mod toml_document;
 #[cfg(feature = "yaml_resources")]
 pub mod yaml;
+#[cfg(feature = "yaml_edit_resources")]
+mod yaml_document;
+
+#[cfg(feature = "serde_resources")]
+use std::ffi::OsStr;
+#[cfg(feature = "serde_resources")]
+use std::path::Path;
+
+#[cfg(any(
+    feature = "json_resources",
+    feature = "toml_resources",
+    feature = "yaml_resources"
+))]
+use crate::error::ResultExt;
+#[cfg(feature = "serde_resources")]
+use crate::format::ErasedFormat;
+#[cfg(feature = "serde_resources")]
+use crate::{AppResError, Format, Resources, Result, Value};
+
+/// The file formats that are enabled through features.
+#[cfg(feature = "serde_resources")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ResourceType {
+    #[cfg(feature = "json_resources")]
+    Json,
+    #[cfg(feature = "toml_resources")]
+    Toml,
+    #[cfg(feature = "yaml_resources")]
+    Yaml,
+}
+
+#[cfg(feature = "serde_resources")]
+impl ResourceType {
+    /// Picks the file format from the extension of the given path.  Returns
+    /// [`AppResError::FormatNotEnabled`] if the format is known but its feature is disabled
+    /// and [`AppResError::UnknownFormat`] otherwise.
+    fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            #[cfg(feature = "json_resources")]
+            Some("json") => Ok(ResourceType::Json),
+            #[cfg(not(feature = "json_resources"))]
+            Some("json") => Err(AppResError::FormatNotEnabled {
+                format: "json",
+                feature: "json_resources",
+            }),
+            #[cfg(feature = "toml_resources")]
+            Some("toml") => Ok(ResourceType::Toml),
+            #[cfg(not(feature = "toml_resources"))]
+            Some("toml") => Err(AppResError::FormatNotEnabled {
+                format: "toml",
+                feature: "toml_resources",
+            }),
+            #[cfg(feature = "yaml_resources")]
+            Some("yaml") | Some("yml") => Ok(ResourceType::Yaml),
+            #[cfg(not(feature = "yaml_resources"))]
+            Some("yaml") | Some("yml") => Err(AppResError::FormatNotEnabled {
+                format: "yaml",
+                feature: "yaml_resources",
+            }),
+            _ => Err(AppResError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+
+    /// Returns the built-in format as a format that can be picked at runtime.
+    fn erased(self) -> &'static dyn ErasedFormat {
+        match self {
+            #[cfg(feature = "json_resources")]
+            ResourceType::Json => &ResourceType::Json,
+            #[cfg(feature = "toml_resources")]
+            ResourceType::Toml => &ResourceType::Toml,
+            #[cfg(feature = "yaml_resources")]
+            ResourceType::Yaml => &ResourceType::Yaml,
+        }
+    }
+}
+
+/// The built-in formats parse values natively, so that errors report where parsing failed.
+#[cfg(feature = "serde_resources")]
+impl ErasedFormat for ResourceType {
+    fn name(&self) -> &str {
+        self.as_format().name()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.as_format().extensions()
+    }
+
+    fn as_format(&self) -> &dyn Format {
+        match *self {
+            #[cfg(feature = "json_resources")]
+            ResourceType::Json => &json::JsonFormat,
+            #[cfg(feature = "toml_resources")]
+            ResourceType::Toml => &toml::TomlFormat,
+            #[cfg(feature = "yaml_resources")]
+            ResourceType::Yaml => &yaml::YamlFormat,
+        }
+    }
+
+    #[cfg_attr(
+        not(any(
+            feature = "json_resources",
+            feature = "toml_resources",
+            feature = "yaml_resources"
+        )),
+        allow(unused_variables)
+    )]
+    fn parse_value(&self, content: &[u8]) -> Result<Value> {
+        match *self {
+            #[cfg(feature = "json_resources")]
+            ResourceType::Json => json::JsonFormat.deserialize(content),
+            #[cfg(feature = "toml_resources")]
+            ResourceType::Toml => toml::TomlFormat.deserialize(content),
+            #[cfg(feature = "yaml_resources")]
+            ResourceType::Yaml => yaml::YamlFormat.deserialize(content),
+        }
+    }
+
+    #[cfg_attr(
+        not(any(feature = "toml_edit_resources", feature = "yaml_edit_resources")),
+        allow(unused_variables)
+    )]
+    fn add_keys(&self, content: &[u8], added: &[(Vec<String>, Value)]) -> Option<Result<Vec<u8>>> {
+        match *self {
+            #[cfg(feature = "json_resources")]
+            ResourceType::Json => None,
+            #[cfg(feature = "toml_edit_resources")]
+            ResourceType::Toml => Some(add_to_document(
+                content,
+                added,
+                |document: &mut toml::TomlDocument, key, value| document.set(key, value),
+            )),
+            #[cfg(all(feature = "toml_resources", not(feature = "toml_edit_resources")))]
+            ResourceType::Toml => None,
+            #[cfg(feature = "yaml_edit_resources")]
+            ResourceType::Yaml => Some(add_to_document(
+                content,
+                added,
+                |document: &mut yaml::YamlDocument, key, value| document.set(key, value),
+            )),
+            #[cfg(all(feature = "yaml_resources", not(feature = "yaml_edit_resources")))]
+            ResourceType::Yaml => None,
+        }
+    }
+}
+
+/// Parses the content into a document, sets the values at the key paths in it and renders
+/// it again.
+#[cfg(any(feature = "toml_edit_resources", feature = "yaml_edit_resources"))]
+fn add_to_document<D>(
+    content: &[u8],
+    added: &[(Vec<String>, Value)],
+    set: impl Fn(&mut D, &str, &Value) -> Result<()>,
+) -> Result<Vec<u8>>
+where
+    D: std::str::FromStr<Err = AppResError> + std::fmt::Display,
+{
+    let content = std::str::from_utf8(content)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    let mut document: D = content.parse()?;
+    for (key_path, value) in added {
+        // Missing keys deserialize as `None` already, and toml has no null.
+        if value.is_null() {
+            continue;
+        }
+        let key: Vec<String> = key_path
+            .iter()
+            .map(|part| format!("\"{}\"", part.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        set(&mut document, &key.join("."), value)?;
+    }
+    Ok(document.to_string().into_bytes())
+}
+
+#[cfg(feature = "serde_resources")]
+impl Resources {
+    /// Read a file from the resources directory and deserialize it with the format matching
+    /// its extension: either a format registered with [`Resources::with_format`] or one of
+    /// the built-in formats (`.json`, `.toml`, `.yaml` or `.yml`) whose feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load and parse the config file in the assets folder, whatever its format
+    /// let config: Config = resources.load("config.toml").unwrap();
+    /// ```
+    pub fn load<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        // Check the format before touching the file.
+        self.format_for(path)?;
+
+        let file_content = self.load_bytes(path)?;
+        self.deserialize_for(path, &file_content)
+    }
+
+    /// Deserialize content with the format that [`Resources::load`] uses for the path.
+    pub(crate) fn deserialize_for<T>(&self, path: &Path, content: &[u8]) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if let Some(format) = self.formats.find(path) {
+            return self.deserialize_with(format.as_format(), path, content);
+        }
+
+        match ResourceType::from_path(path)? {
+            #[cfg(feature = "json_resources")]
+            ResourceType::Json => json::JsonFormat
+                .deserialize(content)
+                .parse_context(&self.get_file_path(path), content),
+            #[cfg(feature = "toml_resources")]
+            ResourceType::Toml => toml::TomlFormat
+                .deserialize(content)
+                .parse_context(&self.get_file_path(path), content),
+            #[cfg(feature = "yaml_resources")]
+            ResourceType::Yaml => yaml::YamlFormat
+                .deserialize(content)
+                .parse_context(&self.get_file_path(path), content),
+        }
+    }
+
+    /// Serialize an object with the format matching the extension of the path and write it
+    /// relative to the resources directory.  The format is picked the same way as in
+    /// [`Resources::load`].
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Write config to the config.yaml file in the assets folder
+    /// let config = Config { stuff: String::from("Hello World") };
+    /// resources.save("config.yaml", &config).unwrap();
+    /// ```
+    pub fn save<C>(&self, path: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let path = path.as_ref();
+        self.save_to_file(path, self.serialize_for(path, thing)?)
+    }
+
+    /// Serialize an object with the format that [`Resources::save`] uses for the path.
+    pub(crate) fn serialize_for<C>(&self, path: &Path, thing: &C) -> Result<Vec<u8>>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        if let Some(format) = self.formats.find(path) {
+            return self.serialize_with(format.as_format(), path, thing);
+        }
+
+        match ResourceType::from_path(path)? {
+            #[cfg(feature = "json_resources")]
+            ResourceType::Json => self.serialize_with(&json::JsonFormat, path, thing),
+            #[cfg(feature = "toml_resources")]
+            ResourceType::Toml => self.serialize_with(&toml::TomlFormat, path, thing),
+            #[cfg(feature = "yaml_resources")]
+            ResourceType::Yaml => self.serialize_with(&yaml::YamlFormat, path, thing),
+        }
+    }
+
+    /// Returns the format used for the path by [`Resources::load`] and [`Resources::save`].
+    pub(crate) fn format_for(&self, path: &Path) -> Result<&dyn ErasedFormat> {
+        match self.formats.find(path) {
+            Some(format) => Ok(format),
+            None => Ok(ResourceType::from_path(path)?.erased()),
+        }
+    }
+}
@@ -1,32 +1,87 @@
-use std::fs::{create_dir_all, write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::{AppResError, Resources, Result};
+use super::ResourceType;
+use crate::error::ResultExt;
+#[cfg(feature = "tokio_resources")]
+use crate::AsyncResources;
+use crate::{
+    save_slice_to_file, AppResError, EnvOverrides, Format, Operation, Resources, Result, Validate,
+    Value,
+};
+
+#[cfg(feature = "toml_edit_resources")]
+pub use super::toml_document::TomlDocument;
 
 pub trait TomlResourcesExt {
     /// Read toml file from resources directory and deserialize it.
-    ///
-    /// Note that the content of the toml file is stored in memory for the duration of
-    /// the [`Resources`] object due to a limitation in the toml library.  Use
-    /// [`load_toml_from_slice`] or [`load_toml_from_str`] if you only need the toml for
-    /// a short period of time.
-    fn load_from_toml_file<'de, T>(&'de mut self, toml_file: impl AsRef<Path>) -> Result<T>
+    fn load_from_toml_file<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned;
+    /// Read toml file from resources directory, deserialize it and check it with
+    /// [`Validate`].
+    fn load_from_toml_file_validated<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate;
+    /// Read toml file from resources directory, apply the environment variable overrides and
+    /// deserialize it.
+    fn load_from_toml_file_with_env<T>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
     where
-        T: serde::Deserialize<'de>;
+        T: serde::de::DeserializeOwned;
+    /// Read toml file from resources directory into a [`BorrowedToml`] that can be
+    /// deserialized into types borrowing from the file content.
+    fn load_borrowed_toml_file(&self, toml_file: impl AsRef<Path>) -> Result<BorrowedToml>;
+    /// Read toml file from resources directory and deserialize it, writing `T::default()` to
+    /// the file first if it does not exist yet.
+    fn load_or_init_toml_file<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned;
+    /// Read toml file from resources directory and deserialize it, writing `default` to the
+    /// file first if it does not exist yet.  If `fill_missing` is set, keys of `default`
+    /// that are missing from an existing file are added to it.
+    fn load_or_init_toml_file_with<T>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned;
     /// Writes toml file to a path relative from the resources directory.
-    fn save_to_toml_file<C: ?Sized>(&self, toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    fn save_to_toml_file<C>(&self, toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized;
+    /// Read toml file from resources directory, let `modify` change it and write it back if
+    /// it changed, starting from `T::default()` if the file does not exist yet.
+    fn update_toml_file<T, F>(&self, toml_file: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T);
+    /// Read toml file from resources directory into a [`TomlDocument`] that can be edited
+    /// key by key without losing its comments and formatting.
+    #[cfg(feature = "toml_edit_resources")]
+    fn load_toml_document(&self, toml_file: impl AsRef<Path>) -> Result<TomlDocument>;
+    /// Writes a [`TomlDocument`] to a path relative from the resources directory.
+    #[cfg(feature = "toml_edit_resources")]
+    fn save_toml_document(
+        &self,
+        toml_file: impl AsRef<Path>,
+        document: &TomlDocument,
+    ) -> Result<()>;
+    /// Read toml file from resources directory into a [`TomlDocument`], let `edit` change
+    /// it and write it back if it changed, keeping its comments and formatting.
+    #[cfg(feature = "toml_edit_resources")]
+    fn edit_toml_file<R, F>(&self, toml_file: impl AsRef<Path>, edit: F) -> Result<R>
     where
-        C: serde::Serialize;
+        F: FnOnce(&mut TomlDocument) -> Result<R>;
 }
 
 impl TomlResourcesExt for Resources {
     /// Read toml file from resources directory and deserialize it.
     ///
-    /// Note that the content of the toml file is stored in memory for the duration of
-    /// the [`Resources`] object due to a limitation in the toml library.  Use
-    /// [`load_toml_from_slice`] or [`load_toml_from_str`] if you only need the toml for
-    /// a short period of time.
-    ///
     /// # Examples
     ///
     /// Basic usage:
@@ -43,20 +98,208 @@ impl TomlResourcesExt for Resources {
     ///     stuff: String,
     /// }
     ///
-    /// let mut resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
     ///
     /// // Load and parse the config.toml file in the assets folder
     /// let config: Config = resources.load_from_toml_file("config.toml").unwrap();
     /// ```
-    fn load_from_toml_file<'de, T>(&'de mut self, toml_file: impl AsRef<Path>) -> Result<T>
+    fn load_from_toml_file<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
     where
-        T: serde::Deserialize<'de>,
+        T: serde::de::DeserializeOwned,
     {
-        let file_content = self.load_from_file(toml_file)?;
-        self.resources.push(file_content);
+        self.load_with(&TomlFormat, toml_file)
+    }
+
+    /// Read toml file from resources directory, deserialize it and check it with
+    /// [`Validate`], failing with [`AppResError::Validation`](crate::AppResError::Validation)
+    /// if any invariant is violated.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::{Resources, Validate, ValidationErrors};
+    /// // Note you need to enable the toml_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// impl Validate for Config {
+    ///     fn validate(&self, errors: &mut ValidationErrors) {
+    ///         if self.port < 1024 {
+    ///             errors.add("port", "must be at least 1024");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load, parse and validate the config.toml file in the assets folder
+    /// let config: Config = resources.load_from_toml_file_validated("config.toml").unwrap();
+    /// ```
+    fn load_from_toml_file_validated<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate,
+    {
+        let toml_file = toml_file.as_ref();
+        let thing = self.load_from_toml_file(toml_file)?;
+        self.validated(toml_file, thing)
+    }
+
+    /// Read toml file from resources directory, apply the environment variable overrides and
+    /// deserialize it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::{EnvOverrides, Resources};
+    /// // Note you need to enable the toml_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load and parse the config.toml file in the assets folder, letting the
+    /// // ASSETS_STUFF environment variable override the stuff field
+    /// let env = EnvOverrides::new("ASSETS");
+    /// let config: Config = resources.load_from_toml_file_with_env("config.toml", &env).unwrap();
+    /// ```
+    fn load_from_toml_file_with_env<T>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let toml_file = toml_file.as_ref();
+        let value: Value = self.load_with(&TomlFormat, toml_file)?;
+        env.apply_and_deserialize(self, toml_file, value)
+    }
+
+    /// Read toml file from resources directory into a [`BorrowedToml`] that can be
+    /// deserialized into types borrowing from the file content.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the toml_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config<'a> {
+    ///     stuff: &'a str,
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load the config.toml file in the assets folder and parse it without copying
+    /// // the strings
+    /// let toml = resources.load_borrowed_toml_file("config.toml").unwrap();
+    /// let config: Config = toml.deserialize().unwrap();
+    /// ```
+    fn load_borrowed_toml_file(&self, toml_file: impl AsRef<Path>) -> Result<BorrowedToml> {
+        let content = self.load_from_file(&toml_file)?;
+        Ok(BorrowedToml {
+            path: self.get_file_path(toml_file),
+            content,
+        })
+    }
 
-        let resource = &self.resources[self.resources.len() - 1];
-        Ok(toml::from_str(resource)?)
+    /// Read toml file from resources directory and deserialize it, writing `T::default()` to
+    /// the file first if it does not exist yet.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the toml_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load and parse the config.toml file, creating it on first run
+    /// let config: Config = resources.load_or_init_toml_file("config.toml").unwrap();
+    /// ```
+    fn load_or_init_toml_file<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_toml_file_with(toml_file, T::default(), false)
+    }
+
+    /// Read toml file from resources directory and deserialize it, writing `default` to the
+    /// file first if it does not exist yet.  If `fill_missing` is set, keys of `default`
+    /// that are missing from an existing file are added to it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the toml_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load and parse the config.toml file, creating it on first run and adding
+    /// // missing fields to it
+    /// let default = Config { stuff: String::from("Hello World") };
+    /// let config: Config = resources
+    ///     .load_or_init_toml_file_with("config.toml", default, true)
+    ///     .unwrap();
+    /// ```
+    fn load_or_init_toml_file_with<T>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_as(
+            ResourceType::Toml.erased(),
+            toml_file.as_ref(),
+            default,
+            fill_missing,
+        )
     }
 
     /// Writes toml file to a path relative from the resources directory.
@@ -83,12 +326,361 @@ impl TomlResourcesExt for Resources {
     /// let config = Config { stuff: String::from("Hello World") };
     /// resources.save_to_toml_file("config.toml", &config).unwrap();
     /// ```
-    fn save_to_toml_file<C: ?Sized>(&self, toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    fn save_to_toml_file<C>(&self, toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        self.save_with(&TomlFormat, toml_file, thing)
+    }
+
+    /// Read toml file from resources directory, let `modify` change it and write it back if
+    /// it changed, starting from `T::default()` if the file does not exist yet.  Returns
+    /// whether the file was written.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the toml_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct State {
+    ///     recent: Vec<String>,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    ///
+    /// // Remember a recently opened project in the state.toml file
+    /// resources
+    ///     .update_toml_file("state.toml", |state: &mut State| {
+    ///         state.recent.push(String::from("rocket"));
+    ///     })
+    ///     .unwrap();
+    /// ```
+    fn update_toml_file<T, F>(&self, toml_file: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        self.update_as(&TomlFormat, toml_file.as_ref(), modify)
+    }
+
+    /// Read toml file from resources directory into a [`TomlDocument`] that can be edited
+    /// key by key without losing its comments and formatting.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    /// // Note you need to enable the toml_edit_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Change the theme in the config.toml file, keeping the comments the user wrote
+    /// let mut document = resources.load_toml_document("config.toml").unwrap();
+    /// document.set("ui.theme", "dark").unwrap();
+    /// resources.save_toml_document("config.toml", &document).unwrap();
+    /// ```
+    #[cfg(feature = "toml_edit_resources")]
+    fn load_toml_document(&self, toml_file: impl AsRef<Path>) -> Result<TomlDocument> {
+        let toml_file = toml_file.as_ref();
+        let file_content = self.load_from_file(toml_file)?;
+        file_content
+            .parse()
+            .parse_context(&self.get_file_path(toml_file), file_content.as_bytes())
+    }
+
+    /// Writes a [`TomlDocument`] to a path relative from the resources directory.
+    ///
+    /// # Examples
+    ///
+    /// See [`TomlResourcesExt::load_toml_document`].
+    #[cfg(feature = "toml_edit_resources")]
+    fn save_toml_document(
+        &self,
+        toml_file: impl AsRef<Path>,
+        document: &TomlDocument,
+    ) -> Result<()> {
+        self.save_to_file(toml_file, document.to_string())
+    }
+
+    /// Read toml file from resources directory into a [`TomlDocument`], let `edit` change
+    /// it and write it back if it changed, keeping its comments and formatting.  A file
+    /// that does not exist yet starts out as an empty document, and is only created if
+    /// `edit` adds something to it.  Returns what `edit` returns; the file is not written
+    /// if `edit` fails.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    /// // Note you need to enable the toml_edit_resources feature in Cargo.toml
+    /// use appres::toml::TomlResourcesExt;
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Bump the port and drop an obsolete key in the config.toml file
+    /// resources
+    ///     .edit_toml_file("config.toml", |document| {
+    ///         let port: u16 = document.get("server.port")?.unwrap_or(8080);
+    ///         document.set("server.port", &(port + 1))?;
+    ///         document.remove("server.legacy_mode")?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "toml_edit_resources")]
+    fn edit_toml_file<R, F>(&self, toml_file: impl AsRef<Path>, edit: F) -> Result<R>
+    where
+        F: FnOnce(&mut TomlDocument) -> Result<R>,
+    {
+        self.edit_document(toml_file.as_ref(), edit)
+    }
+}
+
+/// The content of a toml file, kept around so that it can be deserialized into types that
+/// borrow from it.  Returned by [`TomlResourcesExt::load_borrowed_toml_file`], the content
+/// is freed when this is dropped.
+#[derive(Clone, Debug)]
+pub struct BorrowedToml {
+    path: PathBuf,
+    content: String,
+}
+
+impl BorrowedToml {
+    /// Deserialize the toml, borrowing from the content where the type allows it.
+    pub fn deserialize<'de, T>(&'de self) -> Result<T>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        load_toml_from_str(&self.content).parse_context(&self.path, self.content.as_bytes())
+    }
+
+    /// Returns the absolute path of the toml file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the raw toml.
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the raw toml, consuming the buffer.
+    pub fn into_string(self) -> String {
+        self.content
+    }
+}
+
+#[cfg(feature = "tokio_resources")]
+impl AsyncResources {
+    /// Async version of [`TomlResourcesExt::load_from_toml_file`].
+    pub async fn load_from_toml_file<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_toml_file(toml_file))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::load_from_toml_file_validated`].
+    pub async fn load_from_toml_file_validated<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate + Send + 'static,
+    {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_toml_file_validated(toml_file))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::load_from_toml_file_with_env`].
+    pub async fn load_from_toml_file_with_env<T>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        let env = env.clone();
+        self.run(move |resources| resources.load_from_toml_file_with_env(toml_file, &env))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::load_or_init_toml_file`].
+    pub async fn load_or_init_toml_file<T>(&self, toml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_or_init_toml_file(toml_file))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::load_or_init_toml_file_with`].
+    pub async fn load_or_init_toml_file_with<T>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| {
+            resources.load_or_init_toml_file_with(toml_file, default, fill_missing)
+        })
+        .await
+    }
+
+    /// Async version of [`TomlResourcesExt::load_borrowed_toml_file`].
+    pub async fn load_borrowed_toml_file(
+        &self,
+        toml_file: impl AsRef<Path>,
+    ) -> Result<BorrowedToml> {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_borrowed_toml_file(toml_file))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::save_to_toml_file`].  The object is serialized
+    /// before the file is written on the blocking thread pool.
+    pub async fn save_to_toml_file<C>(&self, toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let toml_file = toml_file.as_ref();
+        let content = self
+            .resources()
+            .serialize_with(&TomlFormat, toml_file, thing)?;
+        self.save_to_file(toml_file, content).await
+    }
+
+    /// Async version of [`TomlResourcesExt::update_toml_file`].
+    pub async fn update_toml_file<T, F>(
+        &self,
+        toml_file: impl AsRef<Path>,
+        modify: F,
+    ) -> Result<bool>
     where
-        C: serde::Serialize,
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T) + Send + 'static,
     {
-        let serialized_thing = toml::to_vec(&thing)?;
-        self.save_to_file(toml_file, serialized_thing)
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.update_toml_file(toml_file, modify))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::load_toml_document`].
+    #[cfg(feature = "toml_edit_resources")]
+    pub async fn load_toml_document(&self, toml_file: impl AsRef<Path>) -> Result<TomlDocument> {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_toml_document(toml_file))
+            .await
+    }
+
+    /// Async version of [`TomlResourcesExt::save_toml_document`].
+    #[cfg(feature = "toml_edit_resources")]
+    pub async fn save_toml_document(
+        &self,
+        toml_file: impl AsRef<Path>,
+        document: &TomlDocument,
+    ) -> Result<()> {
+        self.save_to_file(toml_file, document.to_string()).await
+    }
+
+    /// Async version of [`TomlResourcesExt::edit_toml_file`].
+    #[cfg(feature = "toml_edit_resources")]
+    pub async fn edit_toml_file<R, F>(&self, toml_file: impl AsRef<Path>, edit: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut TomlDocument) -> Result<R> + Send + 'static,
+    {
+        let toml_file = toml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.edit_toml_file(toml_file, edit))
+            .await
+    }
+}
+
+/// The toml [`Format`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use std::collections::BTreeMap;
+///
+/// use appres::Resources;
+/// // Note you need to enable the toml_resources feature in Cargo.toml
+/// use appres::toml::TomlFormat;
+///
+/// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+///
+/// // Load and parse the config.toml file in the assets folder
+/// let config: BTreeMap<String, String> = resources.load_with(&TomlFormat, "config.toml")
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TomlFormat;
+
+impl Format for TomlFormat {
+    fn name(&self) -> &str {
+        "toml"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["toml"]
+    }
+
+    fn serialize(&self, thing: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        match toml::to_vec(thing) {
+            // Maps don't order their tables after their values the way toml requires, so
+            // let toml::Value reorder them.
+            Err(toml::ser::Error::ValueAfterTable) => {
+                Ok(toml::to_vec(&toml::Value::try_from(thing)?)?)
+            }
+            serialized_thing => Ok(serialized_thing?),
+        }
+    }
+
+    fn deserialize_with(
+        &self,
+        content: &[u8],
+        visit: &mut dyn FnMut(&mut dyn erased_serde::Deserializer<'_>) -> Result<()>,
+    ) -> Result<()> {
+        let content = std::str::from_utf8(content)
+            .map_err(|err| AppResError::invalid_format(self.name(), err))?;
+        let mut deserializer = toml::Deserializer::new(content);
+        visit(&mut <dyn erased_serde::Deserializer>::erase(
+            &mut deserializer,
+        ))?;
+        // Check for trailing input the way `toml::from_str` does.
+        #[allow(deprecated)]
+        Ok(deserializer.end()?)
+    }
+}
+
+impl TomlFormat {
+    /// Deserialize an object from bytes, reporting where in them parsing failed.
+    pub(crate) fn deserialize<T>(&self, content: &[u8]) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(toml::from_slice(content)?)
     }
 }
 
@@ -168,13 +760,13 @@ where
 /// let config = Config { stuff: String::from("Hello World") };
 /// save_to_toml_file("config.toml", &config).unwrap();
 /// ```
-pub fn save_to_toml_file<C: ?Sized>(toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+pub fn save_to_toml_file<C>(toml_file: impl AsRef<Path>, thing: &C) -> Result<()>
 where
-    C: serde::Serialize,
+    C: serde::Serialize + ?Sized,
 {
-    let serialized_thing = toml::to_vec(&thing)?;
-
-    let toml_file_dir = toml_file.as_ref().parent().ok_or(AppResError::NoParent)?;
-    create_dir_all(toml_file_dir)?;
-    Ok(write(toml_file, serialized_thing)?)
+    let toml_file = toml_file.as_ref();
+    let serialized_thing = TomlFormat
+        .serialize(&thing)
+        .context(toml_file, Operation::Serialize)?;
+    save_slice_to_file(toml_file, serialized_thing)
 }
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::IntoDeserializer;
+use toml_edit::{DocumentMut, Item, Key, Table, TableLike};
+
+use crate::{AppResError, Result};
+
+/// A toml file loaded for editing, which keeps the comments, key order and whitespace of
+/// the file when individual keys are changed.  Returned by
+/// [`TomlResourcesExt::load_toml_document`](super::toml::TomlResourcesExt::load_toml_document)
+/// and edited in place by
+/// [`TomlResourcesExt::edit_toml_file`](super::toml::TomlResourcesExt::edit_toml_file).
+///
+/// Keys are dotted paths such as `server.port`, and parts containing dots can be quoted as
+/// in toml, e.g. `hosts."example.com".port`.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// // Note you need to enable the toml_edit_resources feature in Cargo.toml
+/// use appres::toml::TomlDocument;
+///
+/// let mut document: TomlDocument = "# The port to listen on\nport = 80\n".parse().unwrap();
+/// document.set("port", &8080).unwrap();
+/// document.set("server.name", "example").unwrap();
+///
+/// assert_eq!(document.get::<u16>("port").unwrap(), Some(8080));
+/// assert_eq!(
+///     document.to_string(),
+///     "# The port to listen on\nport = 8080\n\n[server]\nname = \"example\"\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TomlDocument {
+    document: DocumentMut,
+}
+
+impl TomlDocument {
+    /// Creates an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deserializes the value at the key, or returns `None` if the document does not have
+    /// the key.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut item = self.document.as_item();
+        for part in parse_key(key)? {
+            item = match item.get(part.get()) {
+                Some(item) => item,
+                None => return Ok(None),
+            };
+        }
+
+        let value = match item.clone().into_value() {
+            Ok(value) => value,
+            Err(_) => return Ok(None),
+        };
+        T::deserialize(value.into_deserializer())
+            .map(Some)
+            .map_err(invalid_toml)
+    }
+
+    /// Sets the value at the key, creating the tables along the key that do not exist yet.
+    /// The comments around a value that is replaced are kept.
+    pub fn set<C>(&mut self, key: &str, value: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let parts = parse_key(key)?;
+        let (last, parents) = parts.split_last().ok_or_else(|| empty_key(key))?;
+
+        let mut table: &mut dyn TableLike = self.document.as_table_mut();
+        let mut inline = false;
+        for part in parents {
+            let item = table.entry(part.get()).or_insert_with(|| {
+                let mut parent = Table::new();
+                // Only the tables that end up with values get a header.
+                parent.set_implicit(true);
+                Item::Table(parent)
+            });
+            inline |= item.is_inline_table();
+            table = item
+                .as_table_like_mut()
+                .ok_or_else(|| not_a_table(key, part))?;
+        }
+
+        let value = value
+            .serialize(toml_edit::ser::ValueSerializer::new())
+            .map_err(invalid_toml)?;
+        let mut item = match value {
+            toml_edit::Value::InlineTable(value) if !inline => Item::Table(value.into_table()),
+            value => Item::Value(value),
+        };
+
+        match table.get_mut(last.get()) {
+            Some(existing) => {
+                if let (Some(old), Item::Value(new)) = (existing.as_value(), &mut item) {
+                    *new.decor_mut() = old.decor().clone();
+                }
+                *existing = item;
+            }
+            None => {
+                table.insert(last.get(), item);
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the value at the key, returning whether the document had the key.
+    pub fn remove(&mut self, key: &str) -> Result<bool> {
+        let parts = parse_key(key)?;
+        let (last, parents) = parts.split_last().ok_or_else(|| empty_key(key))?;
+
+        let mut table: &mut dyn TableLike = self.document.as_table_mut();
+        for part in parents {
+            table = match table.get_mut(part.get()).and_then(Item::as_table_like_mut) {
+                Some(table) => table,
+                None => return Ok(false),
+            };
+        }
+        Ok(table.remove(last.get()).is_some())
+    }
+
+    /// Returns the underlying [`toml_edit`] document.
+    pub fn as_document(&self) -> &DocumentMut {
+        &self.document
+    }
+
+    /// Returns the underlying [`toml_edit`] document, for edits that go beyond setting and
+    /// removing keys.
+    pub fn as_document_mut(&mut self) -> &mut DocumentMut {
+        &mut self.document
+    }
+}
+
+impl FromStr for TomlDocument {
+    type Err = AppResError;
+
+    fn from_str(content: &str) -> Result<Self> {
+        Ok(Self {
+            document: content.parse().map_err(invalid_toml)?,
+        })
+    }
+}
+
+impl fmt::Display for TomlDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.document.fmt(f)
+    }
+}
+
+fn parse_key(key: &str) -> Result<Vec<Key>> {
+    Key::parse(key).map_err(invalid_toml)
+}
+
+fn invalid_toml(err: impl std::error::Error + Send + Sync + 'static) -> AppResError {
+    AppResError::invalid_format("toml", err)
+}
+
+fn empty_key(key: &str) -> AppResError {
+    AppResError::invalid_format("toml", format!("invalid key {:?}", key))
+}
+
+fn not_a_table(key: &str, part: &Key) -> AppResError {
+    AppResError::invalid_format(
+        "toml",
+        format!("cannot set {}, {} is not a table", key, part.get()),
+    )
+}
@@ -1,17 +1,78 @@
-use std::fs::{create_dir_all, write};
 use std::path::Path;
 
-use crate::{AppResError, Resources, Result};
+use super::ResourceType;
+use crate::error::ResultExt;
+#[cfg(feature = "tokio_resources")]
+use crate::AsyncResources;
+use crate::{
+    save_slice_to_file, EnvOverrides, Format, Operation, Resources, Result, Validate, Value,
+};
+
+#[cfg(feature = "yaml_edit_resources")]
+pub use super::yaml_document::YamlDocument;
 
 pub trait YamlResourcesExt {
     /// Read yaml file from resources directory and deserialize it.
     fn load_from_yaml_file<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
     where
         T: serde::de::DeserializeOwned;
+    /// Read yaml file from resources directory, deserialize it and check it with
+    /// [`Validate`].
+    fn load_from_yaml_file_validated<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate;
+    /// Read yaml file from resources directory, apply the environment variable overrides and
+    /// deserialize it.
+    fn load_from_yaml_file_with_env<T>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned;
+    /// Read yaml file from resources directory and deserialize it, writing `T::default()` to
+    /// the file first if it does not exist yet.
+    fn load_or_init_yaml_file<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned;
+    /// Read yaml file from resources directory and deserialize it, writing `default` to the
+    /// file first if it does not exist yet.  If `fill_missing` is set, keys of `default`
+    /// that are missing from an existing file are added to it.
+    fn load_or_init_yaml_file_with<T>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned;
     /// Writes yaml file to a path relative from the resources directory.
-    fn save_to_yaml_file<C: ?Sized>(&self, yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    fn save_to_yaml_file<C>(&self, yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized;
+    /// Read yaml file from resources directory, let `modify` change it and write it back if
+    /// it changed, starting from `T::default()` if the file does not exist yet.
+    fn update_yaml_file<T, F>(&self, yaml_file: impl AsRef<Path>, modify: F) -> Result<bool>
     where
-        C: serde::Serialize;
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T);
+    /// Read yaml file from resources directory into a [`YamlDocument`] that can be edited
+    /// key by key without losing its comments and formatting.
+    #[cfg(feature = "yaml_edit_resources")]
+    fn load_yaml_document(&self, yaml_file: impl AsRef<Path>) -> Result<YamlDocument>;
+    /// Writes a [`YamlDocument`] to a path relative from the resources directory.
+    #[cfg(feature = "yaml_edit_resources")]
+    fn save_yaml_document(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        document: &YamlDocument,
+    ) -> Result<()>;
+    /// Read yaml file from resources directory into a [`YamlDocument`], let `edit` change
+    /// it and write it back if it changed, keeping its comments and formatting.
+    #[cfg(feature = "yaml_edit_resources")]
+    fn edit_yaml_file<R, F>(&self, yaml_file: impl AsRef<Path>, edit: F) -> Result<R>
+    where
+        F: FnOnce(&mut YamlDocument) -> Result<R>;
 }
 
 impl YamlResourcesExt for Resources {
@@ -42,8 +103,165 @@ impl YamlResourcesExt for Resources {
     where
         T: serde::de::DeserializeOwned,
     {
-        let file_content = self.load_from_file(yaml_file)?;
-        Ok(serde_yaml::from_str(&file_content)?)
+        self.load_with(&YamlFormat, yaml_file)
+    }
+
+    /// Read yaml file from resources directory, deserialize it and check it with
+    /// [`Validate`], failing with [`AppResError::Validation`](crate::AppResError::Validation)
+    /// if any invariant is violated.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::{Resources, Validate, ValidationErrors};
+    /// // Note you need to enable the yaml_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// impl Validate for Config {
+    ///     fn validate(&self, errors: &mut ValidationErrors) {
+    ///         if self.port < 1024 {
+    ///             errors.add("port", "must be at least 1024");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load, parse and validate the config.yaml file in the assets folder
+    /// let config: Config = resources.load_from_yaml_file_validated("config.yaml").unwrap();
+    /// ```
+    fn load_from_yaml_file_validated<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate,
+    {
+        let yaml_file = yaml_file.as_ref();
+        let thing = self.load_from_yaml_file(yaml_file)?;
+        self.validated(yaml_file, thing)
+    }
+
+    /// Read yaml file from resources directory, apply the environment variable overrides and
+    /// deserialize it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::{EnvOverrides, Resources};
+    /// // Note you need to enable the yaml_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    ///
+    /// // Load and parse the config.yaml file in the assets folder, letting the
+    /// // ASSETS_STUFF environment variable override the stuff field
+    /// let env = EnvOverrides::new("ASSETS");
+    /// let config: Config = resources.load_from_yaml_file_with_env("config.yaml", &env).unwrap();
+    /// ```
+    fn load_from_yaml_file_with_env<T>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let yaml_file = yaml_file.as_ref();
+        let value: Value = self.load_with(&YamlFormat, yaml_file)?;
+        env.apply_and_deserialize(self, yaml_file, value)
+    }
+
+    /// Read yaml file from resources directory and deserialize it, writing `T::default()` to
+    /// the file first if it does not exist yet.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the yaml_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load and parse the config.yaml file, creating it on first run
+    /// let config: Config = resources.load_or_init_yaml_file("config.yaml").unwrap();
+    /// ```
+    fn load_or_init_yaml_file<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_yaml_file_with(yaml_file, T::default(), false)
+    }
+
+    /// Read yaml file from resources directory and deserialize it, writing `default` to the
+    /// file first if it does not exist yet.  If `fill_missing` is set, keys of `default`
+    /// that are missing from an existing file are added to it.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the yaml_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Load and parse the config.yaml file, creating it on first run and adding
+    /// // missing fields to it
+    /// let default = Config { stuff: String::from("Hello World") };
+    /// let config: Config = resources
+    ///     .load_or_init_yaml_file_with("config.yaml", default, true)
+    ///     .unwrap();
+    /// ```
+    fn load_or_init_yaml_file_with<T>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.load_or_init_as(
+            ResourceType::Yaml.erased(),
+            yaml_file.as_ref(),
+            default,
+            fill_missing,
+        )
     }
 
     /// Writes yaml file to a path relative from the resources directory.
@@ -70,12 +288,300 @@ impl YamlResourcesExt for Resources {
     /// let config = Config { stuff: String::from("Hello World") };
     /// resources.save_to_yaml_file("config.yaml", &config).unwrap();
     /// ```
-    fn save_to_yaml_file<C: ?Sized>(&self, yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    fn save_to_yaml_file<C>(&self, yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
     where
-        C: serde::Serialize,
+        C: serde::Serialize + ?Sized,
     {
-        let serialized_thing = serde_yaml::to_vec(&thing)?;
-        self.save_to_file(yaml_file, serialized_thing)
+        self.save_with(&YamlFormat, yaml_file, thing)
+    }
+
+    /// Read yaml file from resources directory, let `modify` change it and write it back if
+    /// it changed, starting from `T::default()` if the file does not exist yet.  Returns
+    /// whether the file was written.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    /// // Note you need to enable the yaml_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct State {
+    ///     recent: Vec<String>,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    ///
+    /// // Remember a recently opened project in the state.yaml file
+    /// resources
+    ///     .update_yaml_file("state.yaml", |state: &mut State| {
+    ///         state.recent.push(String::from("rocket"));
+    ///     })
+    ///     .unwrap();
+    /// ```
+    fn update_yaml_file<T, F>(&self, yaml_file: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        self.update_as(&YamlFormat, yaml_file.as_ref(), modify)
+    }
+
+    /// Read yaml file from resources directory into a [`YamlDocument`] that can be edited
+    /// key by key without losing its comments and formatting.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    /// // Note you need to enable the yaml_edit_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Change the theme in the config.yaml file, keeping the comments the user wrote
+    /// let mut document = resources.load_yaml_document("config.yaml").unwrap();
+    /// document.set("ui.theme", "dark").unwrap();
+    /// resources.save_yaml_document("config.yaml", &document).unwrap();
+    /// ```
+    #[cfg(feature = "yaml_edit_resources")]
+    fn load_yaml_document(&self, yaml_file: impl AsRef<Path>) -> Result<YamlDocument> {
+        let yaml_file = yaml_file.as_ref();
+        let file_content = self.load_from_file(yaml_file)?;
+        file_content
+            .parse()
+            .parse_context(&self.get_file_path(yaml_file), file_content.as_bytes())
+    }
+
+    /// Writes a [`YamlDocument`] to a path relative from the resources directory.
+    ///
+    /// # Examples
+    ///
+    /// See [`YamlResourcesExt::load_yaml_document`].
+    #[cfg(feature = "yaml_edit_resources")]
+    fn save_yaml_document(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        document: &YamlDocument,
+    ) -> Result<()> {
+        self.save_to_file(yaml_file, document.as_str())
+    }
+
+    /// Read yaml file from resources directory into a [`YamlDocument`], let `edit` change
+    /// it and write it back if it changed, keeping its comments and formatting.  A file
+    /// that does not exist yet starts out as an empty document, and is only created if
+    /// `edit` adds something to it.  Returns what `edit` returns; the file is not written
+    /// if `edit` fails.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    /// // Note you need to enable the yaml_edit_resources feature in Cargo.toml
+    /// use appres::yaml::YamlResourcesExt;
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Bump the port and drop an obsolete key in the config.yaml file
+    /// resources
+    ///     .edit_yaml_file("config.yaml", |document| {
+    ///         let port: u16 = document.get("server.port")?.unwrap_or(8080);
+    ///         document.set("server.port", &(port + 1))?;
+    ///         document.remove("server.legacy_mode")?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "yaml_edit_resources")]
+    fn edit_yaml_file<R, F>(&self, yaml_file: impl AsRef<Path>, edit: F) -> Result<R>
+    where
+        F: FnOnce(&mut YamlDocument) -> Result<R>,
+    {
+        self.edit_document(yaml_file.as_ref(), edit)
+    }
+}
+
+#[cfg(feature = "tokio_resources")]
+impl AsyncResources {
+    /// Async version of [`YamlResourcesExt::load_from_yaml_file`].
+    pub async fn load_from_yaml_file<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_yaml_file(yaml_file))
+            .await
+    }
+
+    /// Async version of [`YamlResourcesExt::load_from_yaml_file_validated`].
+    pub async fn load_from_yaml_file_validated<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_from_yaml_file_validated(yaml_file))
+            .await
+    }
+
+    /// Async version of [`YamlResourcesExt::load_from_yaml_file_with_env`].
+    pub async fn load_from_yaml_file_with_env<T>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        env: &EnvOverrides,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        let env = env.clone();
+        self.run(move |resources| resources.load_from_yaml_file_with_env(yaml_file, &env))
+            .await
+    }
+
+    /// Async version of [`YamlResourcesExt::load_or_init_yaml_file`].
+    pub async fn load_or_init_yaml_file<T>(&self, yaml_file: impl AsRef<Path>) -> Result<T>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_or_init_yaml_file(yaml_file))
+            .await
+    }
+
+    /// Async version of [`YamlResourcesExt::load_or_init_yaml_file_with`].
+    pub async fn load_or_init_yaml_file_with<T>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        default: T,
+        fill_missing: bool,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| {
+            resources.load_or_init_yaml_file_with(yaml_file, default, fill_missing)
+        })
+        .await
+    }
+
+    /// Async version of [`YamlResourcesExt::save_to_yaml_file`].  The object is serialized
+    /// before the file is written on the blocking thread pool.
+    pub async fn save_to_yaml_file<C>(&self, yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let yaml_file = yaml_file.as_ref();
+        let content = self
+            .resources()
+            .serialize_with(&YamlFormat, yaml_file, thing)?;
+        self.save_to_file(yaml_file, content).await
+    }
+
+    /// Async version of [`YamlResourcesExt::update_yaml_file`].
+    pub async fn update_yaml_file<T, F>(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        modify: F,
+    ) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T) + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.update_yaml_file(yaml_file, modify))
+            .await
+    }
+
+    /// Async version of [`YamlResourcesExt::load_yaml_document`].
+    #[cfg(feature = "yaml_edit_resources")]
+    pub async fn load_yaml_document(&self, yaml_file: impl AsRef<Path>) -> Result<YamlDocument> {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.load_yaml_document(yaml_file))
+            .await
+    }
+
+    /// Async version of [`YamlResourcesExt::save_yaml_document`].
+    #[cfg(feature = "yaml_edit_resources")]
+    pub async fn save_yaml_document(
+        &self,
+        yaml_file: impl AsRef<Path>,
+        document: &YamlDocument,
+    ) -> Result<()> {
+        self.save_to_file(yaml_file, document.to_string()).await
+    }
+
+    /// Async version of [`YamlResourcesExt::edit_yaml_file`].
+    #[cfg(feature = "yaml_edit_resources")]
+    pub async fn edit_yaml_file<R, F>(&self, yaml_file: impl AsRef<Path>, edit: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut YamlDocument) -> Result<R> + Send + 'static,
+    {
+        let yaml_file = yaml_file.as_ref().to_path_buf();
+        self.run(move |resources| resources.edit_yaml_file(yaml_file, edit))
+            .await
+    }
+}
+
+/// The yaml [`Format`].
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use appres::Resources;
+/// // Note you need to enable the yaml_resources feature in Cargo.toml
+/// use appres::yaml::YamlFormat;
+///
+/// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+///
+/// // Load and parse the list.yaml file in the assets folder
+/// let list: Vec<String> = resources.load_with(&YamlFormat, "list.yaml").unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct YamlFormat;
+
+impl Format for YamlFormat {
+    fn name(&self) -> &str {
+        "yaml"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["yaml", "yml"]
+    }
+
+    fn serialize(&self, thing: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_yaml::to_vec(thing)?)
+    }
+
+    fn deserialize_with(
+        &self,
+        content: &[u8],
+        visit: &mut dyn FnMut(&mut dyn erased_serde::Deserializer<'_>) -> Result<()>,
+    ) -> Result<()> {
+        let deserializer = serde_yaml::Deserializer::from_slice(content);
+        visit(&mut <dyn erased_serde::Deserializer>::erase(deserializer))
+    }
+}
+
+impl YamlFormat {
+    /// Deserialize an object from bytes, reporting where in them parsing failed.
+    pub(crate) fn deserialize<T>(&self, content: &[u8]) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(serde_yaml::from_slice(content)?)
     }
 }
 
@@ -103,7 +609,7 @@ pub fn load_yaml_from_slice<T>(yaml_content: impl AsRef<[u8]>) -> Result<T>
 where
     T: serde::de::DeserializeOwned,
 {
-    Ok(serde_yaml::from_slice(yaml_content.as_ref())?)
+    YamlFormat.deserialize(yaml_content.as_ref())
 }
 
 /// Deserialize a string in yaml format.
@@ -155,13 +661,13 @@ where
 /// let config = Config { stuff: String::from("Hello World") };
 /// save_to_yaml_file("config.yaml", &config).unwrap();
 /// ```
-pub fn save_to_yaml_file<C: ?Sized>(yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
+pub fn save_to_yaml_file<C>(yaml_file: impl AsRef<Path>, thing: &C) -> Result<()>
 where
-    C: serde::Serialize,
+    C: serde::Serialize + ?Sized,
 {
-    let serialized_thing = serde_yaml::to_vec(&thing)?;
-
-    let yaml_file_dir = yaml_file.as_ref().parent().ok_or(AppResError::NoParent)?;
-    create_dir_all(yaml_file_dir)?;
-    Ok(write(yaml_file, serialized_thing)?)
+    let yaml_file = yaml_file.as_ref();
+    let serialized_thing = YamlFormat
+        .serialize(&thing)
+        .context(yaml_file, Operation::Serialize)?;
+    save_slice_to_file(yaml_file, serialized_thing)
 }
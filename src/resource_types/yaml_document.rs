@@ -0,0 +1,601 @@
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+use std::str::FromStr;
+
+use serde_yaml::Value;
+
+use crate::{AppResError, Result};
+
+/// A yaml file loaded for editing, which keeps the comments, anchors, tags, key order and
+/// formatting of the file when individual keys are changed.  Returned by
+/// [`YamlResourcesExt::load_yaml_document`](super::yaml::YamlResourcesExt::load_yaml_document)
+/// and edited in place by
+/// [`YamlResourcesExt::edit_yaml_file`](super::yaml::YamlResourcesExt::edit_yaml_file).
+///
+/// Keys are dotted paths such as `server.port`, and parts containing dots can be put in
+/// double quotes, e.g. `hosts."example.com".port`.  Only the lines of the value that is set
+/// or removed are rewritten, so keys can only be edited inside block mappings: a key inside
+/// a flow mapping such as `server: { port: 80 }` or inside a sequence cannot be edited on
+/// its own, but the whole mapping or sequence can be replaced.  The anchor, tag and
+/// trailing comment of a replaced value are kept.  Edits that would leave the document
+/// invalid, such as removing a value that an alias still refers to or giving a value tagged
+/// `!!int` a string, fail and leave the document unchanged.  Files with more than one
+/// document are not supported, and neither are quoted or plain values of top-level keys
+/// that continue on lines that are not indented.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```
+/// // Note you need to enable the yaml_edit_resources feature in Cargo.toml
+/// use appres::yaml::YamlDocument;
+///
+/// let mut document: YamlDocument = "# The port to listen on\nport: 80  # default\n"
+///     .parse()
+///     .unwrap();
+/// document.set("port", &8080).unwrap();
+/// document.set("server.name", "example").unwrap();
+///
+/// assert_eq!(document.get::<u16>("port").unwrap(), Some(8080));
+/// assert_eq!(
+///     document.to_string(),
+///     "# The port to listen on\nport: 8080  # default\nserver:\n  name: example\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct YamlDocument {
+    content: String,
+}
+
+impl YamlDocument {
+    /// Creates an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deserializes the value at the key, or returns `None` if the document does not have
+    /// the key.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let parts = parse_key(key)?;
+        let document = parse_value(&self.content)?;
+        match lookup(&document, &parts) {
+            Some(value) => Ok(Some(serde_yaml::from_value(value.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets the value at the key, creating the mappings along the key that do not exist
+    /// yet.  New keys are added after the last key of their mapping.
+    pub fn set<C>(&mut self, key: &str, value: &C) -> Result<()>
+    where
+        C: serde::Serialize + ?Sized,
+    {
+        let parts = parse_key(key)?;
+        let value = serde_yaml::to_value(value)?;
+
+        let lines: Vec<&str> = self.content.split_inclusive('\n').collect();
+        let (replaced, new_lines) = match locate(&lines, &parts)? {
+            Location::Found(entry) => (
+                entry.line..entry.end,
+                replace_entry(&lines, &entry, &value)?,
+            ),
+            Location::Missing { found, at, indent } => {
+                (at..at, new_entry(indent, &parts[found..], &value)?)
+            }
+        };
+        self.content = splice(&lines, replaced, new_lines)?;
+        Ok(())
+    }
+
+    /// Removes the value at the key, returning whether the document had the key.
+    pub fn remove(&mut self, key: &str) -> Result<bool> {
+        let parts = parse_key(key)?;
+        if lookup(&parse_value(&self.content)?, &parts).is_none() {
+            return Ok(false);
+        }
+
+        let lines: Vec<&str> = self.content.split_inclusive('\n').collect();
+        match locate(&lines, &parts)? {
+            Location::Found(entry) => {
+                self.content = splice(&lines, entry.line..entry.end, Vec::new())?;
+                Ok(true)
+            }
+            // The key exists, but not on a line of a block mapping.
+            Location::Missing { .. } => Err(not_editable(&parts, parts.len() - 1)),
+        }
+    }
+
+    /// Returns the content of the document.
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+}
+
+impl FromStr for YamlDocument {
+    type Err = AppResError;
+
+    fn from_str(content: &str) -> Result<Self> {
+        parse_value(content)?;
+        Ok(Self {
+            content: content.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for YamlDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.content)
+    }
+}
+
+/// Where a key is in the lines of a document.
+enum Location {
+    /// The key exists.
+    Found(Entry),
+    /// The first `found` parts of the key exist, and the rest go at line `at` with the
+    /// given indentation.
+    Missing {
+        found: usize,
+        at: usize,
+        indent: usize,
+    },
+}
+
+/// A key of a block mapping.
+struct Entry {
+    /// The line of the key.
+    line: usize,
+    /// The indentation of the key.
+    indent: usize,
+    key: String,
+    /// Where the value starts in the line of the key, after the colon.
+    value_start: usize,
+    /// The line after the last line of the value, not counting comments and blank lines
+    /// that follow it.
+    end: usize,
+}
+
+impl Entry {
+    /// Returns the anchor and tag, value and comment on the line of the key.
+    fn value<'a>(&self, lines: &[&'a str]) -> (Option<&'a str>, &'a str, &'a str) {
+        let text = line_text(lines[self.line]);
+        let (value, comment) = split_comment(text[self.value_start..].trim_start());
+        let (properties, value) = strip_properties(value);
+        (properties, value, comment)
+    }
+}
+
+/// The keys of a block mapping.
+struct Block {
+    indent: usize,
+    entries: Vec<Entry>,
+    /// Where new keys go.
+    end: usize,
+}
+
+/// Replaces the lines in the range with new ones, unless that makes the document invalid.
+fn splice(lines: &[&str], replaced: Range<usize>, new_lines: Vec<String>) -> Result<String> {
+    let newline = match lines.first().is_some_and(|line| line.ends_with("\r\n")) {
+        true => "\r\n",
+        false => "\n",
+    };
+
+    let mut content: String = lines[..replaced.start].concat();
+    if !new_lines.is_empty() && !content.is_empty() && !content.ends_with('\n') {
+        content.push_str(newline);
+    }
+    for line in new_lines {
+        content.push_str(&line);
+        content.push_str(newline);
+    }
+    content.push_str(&lines[replaced.end..].concat());
+
+    parse_value(&content)?;
+    Ok(content)
+}
+
+/// Finds the lines of the key, or where its missing parts go.
+fn locate(lines: &[&str], parts: &[String]) -> Result<Location> {
+    let mut range = body(lines)?;
+    let mut indent = 0;
+    for depth in 0..parts.len() {
+        let block = read_block(lines, range.clone(), indent, parts, depth)?;
+        let entry = match block
+            .entries
+            .into_iter()
+            .find(|entry| entry.key == parts[depth])
+        {
+            Some(entry) => entry,
+            None => {
+                return Ok(Location::Missing {
+                    found: depth,
+                    at: block.end,
+                    indent: block.indent,
+                })
+            }
+        };
+        if depth + 1 == parts.len() {
+            return Ok(Location::Found(entry));
+        }
+
+        // Only an empty value can be followed by the lines of a block mapping.
+        let (_, value, _) = entry.value(lines);
+        if !value.is_empty() {
+            return Err(not_editable(parts, depth + 1));
+        }
+        indent = entry.indent + 2;
+        range = entry.line + 1..entry.end;
+    }
+    // `parse_key` never returns an empty key.
+    Err(AppResError::invalid_format("yaml", "empty key"))
+}
+
+/// Returns the lines of the document without the directives and markers around it.
+fn body(lines: &[&str]) -> Result<Range<usize>> {
+    let mut start = 0;
+    for (index, line) in lines.iter().enumerate() {
+        let text = line_text(line);
+        if let Some(rest) = document_marker(text, "---") {
+            if !split_comment(rest.trim()).0.is_empty() {
+                return Err(AppResError::invalid_format(
+                    "yaml",
+                    "the document is not a block mapping",
+                ));
+            }
+            start = index + 1;
+            break;
+        }
+        if !text.starts_with('%') && significant(line).is_some() {
+            break;
+        }
+    }
+
+    let end = lines[start..]
+        .iter()
+        .position(|line| {
+            let text = line_text(line);
+            document_marker(text, "---").is_some() || document_marker(text, "...").is_some()
+        })
+        .map_or(lines.len(), |position| start + position);
+    Ok(start..end)
+}
+
+/// Reads the keys of the block mapping in the lines of the range, which is the value of
+/// the first `depth` parts of the key.
+fn read_block(
+    lines: &[&str],
+    range: Range<usize>,
+    indent: usize,
+    parts: &[String],
+    depth: usize,
+) -> Result<Block> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut block_indent = None;
+    let mut in_block_scalar = false;
+    for index in range.clone() {
+        // Lines of literal and folded scalars that look like comments are content.
+        if let Some(entry) = entries.last_mut().filter(|_| in_block_scalar) {
+            let text = line_text(lines[index]);
+            let content = text.trim_start_matches(' ');
+            if !content.trim().is_empty() && text.len() - content.len() > entry.indent {
+                entry.end = index + 1;
+                continue;
+            }
+        }
+
+        let (line_indent, text) = match significant(lines[index]) {
+            Some(line) => line,
+            None => continue,
+        };
+        let block_indent = *block_indent.get_or_insert(line_indent);
+
+        // Sequences may be indented as much as the key they belong to.
+        let continues_value =
+            line_indent > block_indent || (line_indent == block_indent && is_sequence_item(text));
+        if continues_value {
+            if let Some(entry) = entries.last_mut() {
+                entry.end = index + 1;
+                continue;
+            }
+        }
+
+        let key = match line_indent == block_indent {
+            true => mapping_key(text),
+            false => None,
+        };
+        match key {
+            Some((key, value_start)) => {
+                let entry = Entry {
+                    line: index,
+                    indent: line_indent,
+                    key,
+                    value_start: line_indent + value_start,
+                    end: index + 1,
+                };
+                let (_, value, _) = entry.value(lines);
+                in_block_scalar = value.starts_with(&['|', '>'][..]);
+                entries.push(entry);
+            }
+            None => return Err(not_editable(parts, depth)),
+        }
+    }
+
+    Ok(Block {
+        indent: block_indent.unwrap_or(indent),
+        end: entries.last().map_or(range.end, |entry| entry.end),
+        entries,
+    })
+}
+
+/// Renders the lines that replace an entry, keeping its key, anchor, tag and comment.
+fn replace_entry(lines: &[&str], entry: &Entry, value: &Value) -> Result<Vec<String>> {
+    let (properties, _, comment) = entry.value(lines);
+    render_entry(
+        &line_text(lines[entry.line])[..entry.value_start],
+        properties,
+        value,
+        comment,
+        entry.indent + 2,
+    )
+}
+
+/// Renders the lines of the missing parts of a key, indented by `indent`.
+fn new_entry(indent: usize, parts: &[String], value: &Value) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut indent = indent;
+    for (index, part) in parts.iter().enumerate() {
+        let head = format!("{}{}:", " ".repeat(indent), render_key(part)?);
+        if index + 1 == parts.len() {
+            lines.extend(render_entry(&head, None, value, "", indent + 2)?);
+        } else {
+            lines.push(head);
+            indent += 2;
+        }
+    }
+    Ok(lines)
+}
+
+/// Renders a key followed by a value, which is put on the following lines if it is a
+/// non-empty mapping or sequence.
+fn render_entry(
+    head: &str,
+    properties: Option<&str>,
+    value: &Value,
+    comment: &str,
+    child_indent: usize,
+) -> Result<Vec<String>> {
+    let rendered = to_yaml(value)?;
+    let mut rendered = rendered.lines();
+
+    let mut first = head.to_string();
+    if let Some(properties) = properties {
+        first.push(' ');
+        first.push_str(properties);
+    }
+    let nested = match value {
+        Value::Mapping(mapping) => !mapping.is_empty(),
+        Value::Sequence(sequence) => !sequence.is_empty(),
+        _ => false,
+    };
+    if !nested {
+        first.push(' ');
+        first.push_str(rendered.next().unwrap_or_default());
+    }
+    if !comment.is_empty() {
+        if !comment.starts_with(char::is_whitespace) {
+            first.push(' ');
+        }
+        first.push_str(comment);
+    }
+
+    let padding = " ".repeat(child_indent);
+    Ok(iter::once(first)
+        .chain(rendered.map(|line| format!("{}{}", padding, line)))
+        .collect())
+}
+
+fn render_key(key: &str) -> Result<String> {
+    to_yaml(&Value::String(key.to_string()))
+}
+
+fn to_yaml(value: &Value) -> Result<String> {
+    let rendered = serde_yaml::to_string(value)?;
+    let rendered = rendered.strip_prefix("---\n").unwrap_or(&rendered);
+    Ok(rendered.trim_end_matches('\n').to_string())
+}
+
+/// Parses the document, treating one without any content as null.
+fn parse_value(content: &str) -> Result<Value> {
+    if content
+        .split_inclusive('\n')
+        .all(|line| significant(line).is_none())
+    {
+        return Ok(Value::Null);
+    }
+    Ok(serde_yaml::from_str(content)?)
+}
+
+fn lookup<'a>(document: &'a Value, parts: &[String]) -> Option<&'a Value> {
+    parts.iter().try_fold(document, |value, part| {
+        let mapping = value.as_mapping()?;
+        mapping.get(&Value::String(part.clone())).or_else(|| {
+            // Keys such as 8080 or true are not strings.
+            let key = serde_yaml::from_str::<Value>(part).ok()?;
+            mapping.get(&key)
+        })
+    })
+}
+
+/// Splits a dotted key into its parts.
+fn parse_key(key: &str) -> Result<Vec<String>> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut quoted = false;
+    let mut chars = key.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => part.extend(chars.next()),
+            '.' if !quoted => parts.push(std::mem::take(&mut part)),
+            c => part.push(c),
+        }
+    }
+    parts.push(part);
+
+    if quoted || parts.iter().any(String::is_empty) {
+        return Err(AppResError::invalid_format(
+            "yaml",
+            format!("invalid key {:?}", key),
+        ));
+    }
+    Ok(parts)
+}
+
+/// Returns the line without its line break.
+fn line_text(line: &str) -> &str {
+    line.trim_end_matches(&['\n', '\r'][..])
+}
+
+/// Returns the indentation and content of a line, or `None` for blank lines and comments.
+fn significant(line: &str) -> Option<(usize, &str)> {
+    let text = line_text(line);
+    let content = text.trim_start_matches(' ');
+    if content.trim().is_empty() || content.starts_with('#') {
+        return None;
+    }
+    Some((text.len() - content.len(), content))
+}
+
+/// Returns what follows a `---` or `...` marker at the start of the line.
+fn document_marker<'a>(text: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(marker)?;
+    match rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        true => Some(rest),
+        false => None,
+    }
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ") || text.starts_with("-\t")
+}
+
+/// Parses the key of a line of a block mapping, returning it along with where its value
+/// starts.
+fn mapping_key(text: &str) -> Option<(String, usize)> {
+    if is_sequence_item(text) || text.starts_with(&['?', '[', '{', '&', '*', '!', '|', '>'][..]) {
+        return None;
+    }
+
+    let after_colon = |colon: usize| {
+        let rest = &text[colon + 1..];
+        rest.is_empty() || rest.starts_with(char::is_whitespace)
+    };
+    if text.starts_with(&['"', '\''][..]) {
+        let close = closing_quote(text)?;
+        if !text[close + 1..].starts_with(':') || !after_colon(close + 1) {
+            return None;
+        }
+        let key = match text.starts_with('"') {
+            true => serde_yaml::from_str(&text[..=close]).ok()?,
+            false => text[1..close].replace("''", "'"),
+        };
+        return Some((key, close + 2));
+    }
+
+    for (index, c) in text.char_indices() {
+        match c {
+            ':' if after_colon(index) => {
+                let key = text[..index].trim_end();
+                return match key.is_empty() {
+                    true => None,
+                    false => Some((key.to_string(), index + 1)),
+                };
+            }
+            '#' if text[..index].ends_with(char::is_whitespace) => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the index of the quote that closes the quoted scalar at the start of the text.
+fn closing_quote(text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => {
+                chars.next();
+            }
+            c if c == quote => {
+                // Single quotes are escaped by doubling them.
+                if quote == '\'' && chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                    chars.next();
+                    continue;
+                }
+                return Some(index);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a value from the comment after it, which keeps the whitespace before the `#`.
+fn split_comment(value: &str) -> (&str, &str) {
+    let mut comment_start = None;
+    if value.starts_with('#') {
+        comment_start = Some(0);
+    } else if value.starts_with(&['"', '\''][..]) {
+        if let Some(close) = closing_quote(value) {
+            comment_start = value[close + 1..]
+                .find(" #")
+                .or_else(|| value[close + 1..].find("\t#"))
+                .map(|index| close + 1 + index);
+        }
+    } else {
+        comment_start = value
+            .char_indices()
+            .find(|&(index, c)| c == '#' && value[..index].ends_with(char::is_whitespace))
+            .map(|(index, _)| index);
+    }
+
+    match comment_start {
+        Some(index) => {
+            let content = value[..index].trim_end();
+            (content, &value[content.len()..])
+        }
+        None => (value, ""),
+    }
+}
+
+/// Splits the anchor and tag, in either order, off the start of a value.
+fn strip_properties(value: &str) -> (Option<&str>, &str) {
+    let mut end = 0;
+    for _ in 0..2 {
+        let rest = value[end..].trim_start();
+        if !rest.starts_with(&['&', '!'][..]) {
+            break;
+        }
+        end = value.len() - rest.len();
+        end += rest.find(char::is_whitespace).unwrap_or(rest.len());
+    }
+    match end {
+        0 => (None, value),
+        end => (Some(&value[..end]), value[end..].trim_start()),
+    }
+}
+
+/// The error for a key whose first `depth` parts do not name a block mapping.
+fn not_editable(parts: &[String], depth: usize) -> AppResError {
+    let message = match depth {
+        0 => String::from("the document is not a block mapping"),
+        depth => format!("{} is not a block mapping", parts[..depth].join(".")),
+    };
+    AppResError::invalid_format("yaml", message)
+}
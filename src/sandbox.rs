@@ -0,0 +1,73 @@
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use crate::Result;
+
+/// Removes `.` and `..` components from a relative path without touching the disk.  Returns
+/// `None` for absolute paths and for paths whose `..` components climb above the start.
+pub(crate) fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::Prefix(_) | Component::RootDir => return None,
+        }
+    }
+    Some(normalized)
+}
+
+/// Checks that a normalized relative path stays inside the root once its symlinks are
+/// resolved.  The components that exist on disk are resolved one at a time, so symlinks
+/// that point outside the root are caught even if the file itself does not exist yet.
+pub(crate) fn stays_inside(root: &Path, path: &Path) -> Result<bool> {
+    let canonical_root = match root.canonicalize() {
+        Ok(canonical_root) => canonical_root,
+        // Nothing below a missing root exists, so there are no symlinks to follow.
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(true),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut resolved = canonical_root.clone();
+    for name in path.components() {
+        let next = resolved.join(name);
+        match next.symlink_metadata() {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                resolved = match next.canonicalize() {
+                    Ok(target) => target,
+                    // A dangling symlink would be followed when the file is written.
+                    Err(_) => normalize_absolute(&resolved.join(next.read_link()?)),
+                };
+            }
+            Ok(_) => resolved = next,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(err.into()),
+        }
+
+        if !resolved.starts_with(&canonical_root) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Removes `.` and `..` components from an absolute path without touching the disk.
+fn normalize_absolute(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
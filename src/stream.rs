@@ -0,0 +1,207 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::atomic::PendingFile;
+use crate::backend::Backend;
+use crate::error::ResultExt;
+use crate::lock::{FileLock, LockKind};
+use crate::{Operation, Resources, Result, WriteMode};
+
+impl Resources {
+    /// Loads a file at the path specified relative to the directory that was given when
+    /// the resource manager was created as bytes, for files that are not text such as
+    /// images or fonts.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::Resources;
+    ///
+    /// // Read the icon.png file in the assets folder
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let icon = resources.load_bytes("icon.png").unwrap();
+    /// ```
+    pub fn load_bytes(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        let path = path.as_ref();
+        let _lock = self.auto_lock(path, LockKind::Shared)?;
+
+        self.backend
+            .read(&self.backend_path(path)?)
+            .context(&self.get_file_path(path), Operation::Read)
+    }
+
+    /// Opens a file at the path specified relative to the directory that was given when
+    /// the resource manager was created for reading.  Files on disk are streamed, files in
+    /// other backends are read in full first.  If locking is enabled with
+    /// [`Resources::with_locking`], a shared lock is held on the file until the reader is
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use std::io::{BufRead, BufReader};
+    ///
+    /// use appres::Resources;
+    ///
+    /// // Count the lines of the words.txt file in the assets folder
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let words = BufReader::new(resources.open_reader("words.txt").unwrap());
+    /// let count = words.lines().count();
+    /// ```
+    pub fn open_reader(&self, path: impl AsRef<Path>) -> Result<Box<dyn Read + Send>> {
+        let path = path.as_ref();
+        let backend_path = self.backend_path(path)?;
+        let lock = self.auto_lock(path, LockKind::Shared)?;
+
+        // Files that a backend overlays on top of the disk are not on disk.
+        if let Some(file_path) = self.backend.local_path(&backend_path) {
+            if file_path.is_file() {
+                let file = File::open(&file_path).context(&file_path, Operation::Read)?;
+                return Ok(Box::new(LockedReader { file, _lock: lock }));
+            }
+        }
+
+        let content = self
+            .backend
+            .read(&backend_path)
+            .context(&self.get_file_path(path), Operation::Read)?;
+        Ok(Box::new(Cursor::new(content)))
+    }
+
+    /// Opens a file at the path specified relative to the directory that was given when
+    /// the resource manager was created for writing.  The content is only guaranteed to be
+    /// in place once [`ResourceWriter::finish`] is called.  With the default
+    /// [`WriteMode::Atomic`], dropping the writer without finishing it leaves the file as
+    /// it was.
+    ///
+    /// If locking is enabled with [`Resources::with_locking`], an exclusive lock is held on
+    /// the file until the writer is finished or dropped, so the resource manager must not
+    /// load or save the file meanwhile.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use std::io::Write;
+    ///
+    /// use appres::Resources;
+    ///
+    /// // Write the numbers from 0 to 999 to the numbers.txt file in the assets folder
+    /// let resources = Resources::new_dir_relative_to_executable("assets").unwrap();
+    /// let mut numbers = resources.open_writer("numbers.txt").unwrap();
+    /// for number in 0..1000 {
+    ///     writeln!(numbers, "{}", number).unwrap();
+    /// }
+    /// numbers.finish().unwrap();
+    /// ```
+    pub fn open_writer(&self, path: impl AsRef<Path>) -> Result<ResourceWriter> {
+        let path = path.as_ref();
+        let backend_path = self.backend_path(path)?;
+        let file_path = self.get_file_path(path);
+        let lock = self.auto_lock(path, LockKind::Exclusive)?;
+
+        let target = match self.backend.local_path(&backend_path) {
+            Some(local_path) => {
+                let file = PendingFile::create(&local_path, self.write_mode)
+                    .context(&local_path, Operation::Write)?;
+                Target::File(BufWriter::new(file))
+            }
+            None => Target::Buffer {
+                content: Vec::new(),
+                backend: self.backend.clone(),
+                path: backend_path,
+                write_mode: self.write_mode,
+            },
+        };
+
+        Ok(ResourceWriter {
+            target,
+            file_path,
+            _lock: lock,
+        })
+    }
+}
+
+/// A file on disk being read, along with the lock that keeps it from being replaced.
+struct LockedReader {
+    file: File,
+    _lock: Option<FileLock>,
+}
+
+impl Read for LockedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+/// A file being written, returned by [`Resources::open_writer`].
+pub struct ResourceWriter {
+    target: Target,
+    file_path: PathBuf,
+    /// Released once the file is in place.
+    _lock: Option<FileLock>,
+}
+
+enum Target {
+    File(BufWriter<PendingFile>),
+    /// Backends that are not on disk get the content in one go when the writer finishes.
+    Buffer {
+        content: Vec<u8>,
+        backend: Arc<dyn Backend>,
+        path: PathBuf,
+        write_mode: WriteMode,
+    },
+}
+
+impl ResourceWriter {
+    /// Flushes the content and puts the file in place.
+    pub fn finish(self) -> Result<()> {
+        let file_path = self.file_path;
+
+        match self.target {
+            Target::File(file) => file
+                .into_inner()
+                .map_err(|err| err.into_error().into())
+                .and_then(PendingFile::commit),
+            Target::Buffer {
+                content,
+                backend,
+                path,
+                write_mode,
+            } => backend.write(&path, &content, write_mode),
+        }
+        .context(&file_path, Operation::Write)
+    }
+}
+
+impl Write for ResourceWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.target {
+            Target::File(file) => file.write(buf),
+            Target::Buffer { content, .. } => content.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.target {
+            Target::File(file) => file.flush(),
+            Target::Buffer { .. } => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for ResourceWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceWriter")
+            .field("file_path", &self.file_path)
+            .finish()
+    }
+}
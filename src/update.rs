@@ -0,0 +1,160 @@
+#[cfg(any(feature = "toml_edit_resources", feature = "yaml_edit_resources"))]
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+#[cfg(any(feature = "toml_edit_resources", feature = "yaml_edit_resources"))]
+use crate::error::ResultExt;
+use crate::lock::LockKind;
+#[cfg(any(feature = "toml_edit_resources", feature = "yaml_edit_resources"))]
+use crate::{AppResError, Operation};
+use crate::{Format, LockOptions, Resources, Result};
+
+impl Resources {
+    /// Loads a file from the resources directory, lets `modify` change its content and
+    /// saves it again if it changed.  A file that does not exist yet starts out as
+    /// `T::default()`, and is only created if `modify` changes the default.  The format is
+    /// picked from the extension the same way as in [`Resources::load`].  Returns whether
+    /// the file was saved.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Default, Deserialize, Serialize)]
+    /// struct State {
+    ///     recent: Vec<String>,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_state("projectile").unwrap();
+    ///
+    /// // Remember a recently opened project in state.yaml.
+    /// resources
+    ///     .update("state.yaml", |state: &mut State| {
+    ///         state.recent.push(String::from("rocket"));
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn update<T, F>(&self, path: impl AsRef<Path>, modify: F) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        let path = path.as_ref();
+        let format = self.format_for(path)?;
+        self.update_as(format.as_format(), path, modify)
+    }
+
+    /// Implements `update` for the given format.
+    pub(crate) fn update_as<T, F>(
+        &self,
+        format: &dyn Format,
+        path: &Path,
+        modify: F,
+    ) -> Result<bool>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        let ((), changed) = self.modify_as(format, path, self.locking, modify)?;
+        Ok(changed)
+    }
+
+    /// Loads a file with the format, lets `modify` change the object and saves it again if
+    /// its serialized form changed.  See [`Resources::modify_file`].
+    pub(crate) fn modify_as<T, R, F>(
+        &self,
+        format: &dyn Format,
+        path: &Path,
+        locking: Option<LockOptions>,
+        modify: F,
+    ) -> Result<(R, bool)>
+    where
+        T: Default + serde::Serialize + serde::de::DeserializeOwned,
+        F: FnOnce(&mut T) -> R,
+    {
+        self.modify_file(
+            path,
+            locking,
+            |content| match content {
+                Some(content) => self.deserialize_with(format, path, &content),
+                None => Ok(T::default()),
+            },
+            |thing| Ok(modify(thing)),
+            |thing| self.serialize_with(format, path, thing),
+        )
+    }
+
+    /// Loads a file, lets `modify` change it and saves it again if it changed, for the
+    /// methods that do so.  `parse` turns the content of the file, or `None` if it does not
+    /// exist yet, into what `modify` changes, and `render` turns that back into content.  The
+    /// file is saved if what is rendered after `modify` differs from what is rendered
+    /// before, so formatting alone does not count as a change and missing files are only
+    /// created if `modify` changes something.  Returns what `modify` returns and whether
+    /// the file was saved.
+    ///
+    /// The file is held under an exclusive lock from loading to saving if `locking` is set,
+    /// so that instances of an application doing the same do not overwrite each other's
+    /// changes, and the load and save themselves do not lock again.
+    pub(crate) fn modify_file<D, R>(
+        &self,
+        path: &Path,
+        locking: Option<LockOptions>,
+        parse: impl FnOnce(Option<Vec<u8>>) -> Result<D>,
+        modify: impl FnOnce(&mut D) -> Result<R>,
+        render: impl Fn(&D) -> Result<Vec<u8>>,
+    ) -> Result<(R, bool)> {
+        let _lock = match locking {
+            Some(options) => Some(self.lock(path, LockKind::Exclusive, options)?),
+            None => None,
+        };
+        let unlocked = self.without_locking();
+
+        let content = match unlocked.load_bytes(path) {
+            Ok(content) => Some(content),
+            Err(err) if err.is_not_found() => None,
+            Err(err) => return Err(err),
+        };
+        let mut data = parse(content)?;
+
+        let before = render(&data)?;
+        let result = modify(&mut data)?;
+        let after = render(&data)?;
+        if before == after {
+            return Ok((result, false));
+        }
+
+        unlocked.save_to_file(path, after)?;
+        Ok((result, true))
+    }
+
+    /// Loads a text file into a document that keeps its formatting, lets `edit` change it
+    /// and saves it again if it changed, using [`Resources::modify_file`].  A file that does
+    /// not exist yet starts out as an empty document.
+    #[cfg(any(feature = "toml_edit_resources", feature = "yaml_edit_resources"))]
+    pub(crate) fn edit_document<D, R>(
+        &self,
+        path: &Path,
+        edit: impl FnOnce(&mut D) -> Result<R>,
+    ) -> Result<R>
+    where
+        D: std::str::FromStr<Err = AppResError> + std::fmt::Display,
+    {
+        let file_path = self.get_file_path(path);
+        let parse = |content: Option<Vec<u8>>| {
+            let content = String::from_utf8(content.unwrap_or_default())
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+                .context(&file_path, Operation::Read)?;
+            content
+                .parse()
+                .parse_context(&file_path, content.as_bytes())
+        };
+        let render = |document: &D| Ok(document.to_string().into_bytes());
+        let (result, _) = self.modify_file(path, self.locking, parse, edit, render)?;
+        Ok(result)
+    }
+}
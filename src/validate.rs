@@ -0,0 +1,191 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::error::ResultExt;
+use crate::{AppResError, Operation, Resources, Result};
+
+/// Checks invariants of a loaded object that serde cannot express, such as port ranges or
+/// lists that must not be empty.  Used by [`Resources::load_validated`] and the
+/// `_validated` methods of the format extension traits, which fail with
+/// [`AppResError::Validation`] listing every violation found.
+///
+/// # Examples
+///
+/// Basic usage:
+///
+/// ```no_run
+/// use serde::Deserialize;
+///
+/// use appres::{Resources, Validate, ValidationErrors};
+///
+/// #[derive(Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// impl Validate for Server {
+///     fn validate(&self, errors: &mut ValidationErrors) {
+///         if self.port < 1024 {
+///             errors.add("port", "must be at least 1024");
+///         }
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     servers: Vec<Server>,
+/// }
+///
+/// impl Validate for Config {
+///     fn validate(&self, errors: &mut ValidationErrors) {
+///         if self.servers.is_empty() {
+///             errors.add("servers", "must not be empty");
+///         }
+///         // Reports e.g. servers[1].port
+///         errors.nested("servers", &self.servers);
+///     }
+/// }
+///
+/// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+/// let config: Config = resources.load_validated("config.toml").unwrap();
+/// ```
+pub trait Validate {
+    /// Adds every violated invariant to `errors`.
+    fn validate(&self, errors: &mut ValidationErrors);
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        if let Some(thing) = self {
+            thing.validate(errors);
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        for (index, thing) in self.iter().enumerate() {
+            errors.nested(format!("[{}]", index), thing);
+        }
+    }
+}
+
+impl<T: Validate> Validate for Box<T> {
+    fn validate(&self, errors: &mut ValidationErrors) {
+        self.as_ref().validate(errors);
+    }
+}
+
+/// A violated invariant found by [`Validate::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// Path of the offending field, e.g. `servers[1].port`.  Empty for the object itself.
+    pub path: String,
+    /// What is wrong with the field.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// The violations collected by [`Validate::validate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    violations: Vec<Violation>,
+}
+
+impl ValidationErrors {
+    /// Creates an empty list of violations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a violation for the field at the path.
+    pub fn add(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.violations.push(Violation {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Validates a field that implements [`Validate`] itself, prefixing the paths of its
+    /// violations with the path of the field.
+    pub fn nested<T>(&mut self, path: impl AsRef<str>, thing: &T)
+    where
+        T: Validate + ?Sized,
+    {
+        let mut nested = ValidationErrors::new();
+        thing.validate(&mut nested);
+
+        let prefix = path.as_ref();
+        for mut violation in nested.violations {
+            violation.path = match violation.path.as_str() {
+                "" => prefix.to_string(),
+                // Indices attach to the field they index into.
+                path if path.starts_with('[') => format!("{}{}", prefix, path),
+                path => format!("{}.{}", prefix, path),
+            };
+            self.violations.push(violation);
+        }
+    }
+
+    /// Returns whether no violations were found.
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns the violations in the order they were found.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, violation) in self.violations.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Resources {
+    /// Same as [`Resources::load`], but also checks the loaded object with
+    /// [`Validate::validate`] and fails with [`AppResError::Validation`] if any invariant
+    /// is violated.
+    ///
+    /// # Examples
+    ///
+    /// See [`Validate`].
+    pub fn load_validated<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned + Validate,
+    {
+        let path = path.as_ref();
+        let thing = self.load(path)?;
+        self.validated(path, thing)
+    }
+
+    /// Returns the object if it is valid, the violations as an error otherwise.
+    pub(crate) fn validated<T>(&self, path: &Path, thing: T) -> Result<T>
+    where
+        T: Validate,
+    {
+        let mut errors = ValidationErrors::new();
+        thing.validate(&mut errors);
+        if errors.is_empty() {
+            return Ok(thing);
+        }
+        Err(AppResError::Validation(errors)).context(&self.get_file_path(path), Operation::Validate)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::Value;
+
+/// Merges `overlay` into `base`.  Maps are merged key by key, everything else in `overlay`
+/// replaces what is in `base`.
+pub(crate) fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Returns the key path of every leaf in `value`, a leaf being anything that is not a
+/// non-empty map.
+pub(crate) fn leaf_paths(value: &Value) -> Vec<Vec<String>> {
+    let mut paths = vec![];
+    collect_leaf_paths(value, &mut vec![], &mut paths);
+    paths
+}
+
+fn collect_leaf_paths(value: &Value, prefix: &mut Vec<String>, paths: &mut Vec<Vec<String>>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                prefix.push(key.clone());
+                collect_leaf_paths(value, prefix, paths);
+                prefix.pop();
+            }
+        }
+        _ => paths.push(prefix.clone()),
+    }
+}
+
+/// Looks up the value at the given key path.
+pub(crate) fn get_path<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.as_object()?.get(key))
+}
+
+/// Copies the keys of `defaults` that are missing from `target` into it, recursing into maps
+/// that both have.  Returns the key paths that were added along with their values.
+pub(crate) fn fill_missing(target: &mut Value, defaults: Value) -> Vec<(Vec<String>, Value)> {
+    let mut added = vec![];
+    fill_missing_below(target, defaults, &mut vec![], &mut added);
+    added
+}
+
+fn fill_missing_below(
+    target: &mut Value,
+    defaults: Value,
+    prefix: &mut Vec<String>,
+    added: &mut Vec<(Vec<String>, Value)>,
+) {
+    let (target, defaults) = match (target, defaults) {
+        (Value::Object(target), Value::Object(defaults)) => (target, defaults),
+        _ => return,
+    };
+
+    for (key, default) in defaults {
+        prefix.push(key.clone());
+        match target.get_mut(&key) {
+            Some(existing) => fill_missing_below(existing, default, prefix, added),
+            None => {
+                added.push((prefix.clone(), default.clone()));
+                target.insert(key, default);
+            }
+        }
+        prefix.pop();
+    }
+}
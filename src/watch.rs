@@ -0,0 +1,229 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{Resources, Result};
+
+/// Options for [`Resources::watch_with`].
+#[derive(Clone, Copy, Debug)]
+pub struct WatchOptions {
+    poll_interval: Duration,
+    debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(250),
+            debounce: Duration::from_millis(100),
+        }
+    }
+}
+
+impl WatchOptions {
+    /// Sets how often the file is checked for changes.  Defaults to 250 milliseconds.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets how long the file has to stay unchanged before it is parsed again.  This lets
+    /// editors finish saving (e.g. truncating and then writing, or writing a temporary file
+    /// and renaming it over the original) before the file is read.  Defaults to 100
+    /// milliseconds.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+}
+
+/// Handle to a file being watched.  The file stops being watched when this is dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Stops watching the file.  Same as dropping the watcher.
+    pub fn stop(self) {}
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        // Disconnecting the channel wakes the watching thread up and makes it exit.
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// What is known about the file at one point in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Fingerprint {
+    fn of(resources: &Resources, path: &Path) -> Option<Self> {
+        let metadata = resources.metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified,
+            len: metadata.len,
+        })
+    }
+}
+
+impl Resources {
+    /// Watches a file relative to the resources directory and calls `on_change` with the
+    /// newly parsed content every time it changes.  The format is picked from the extension
+    /// the same way as in [`Resources::load`].
+    ///
+    /// Changes are debounced, and content that fails to parse (e.g. a file that is only
+    /// half written) or that is identical to the last delivered content is skipped.  The
+    /// callback runs on a background thread until the returned [`Watcher`] is dropped.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    ///
+    /// use appres::Resources;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     stuff: String,
+    /// }
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Print the new stuff whenever the user edits config.toml.
+    /// let watcher = resources
+    ///     .watch("config.toml", |config: Config| println!("{}", config.stuff))
+    ///     .unwrap();
+    /// ```
+    pub fn watch<T, F>(&self, path: impl AsRef<Path>, on_change: F) -> Result<Watcher>
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnMut(T) + Send + 'static,
+    {
+        self.watch_with(path, WatchOptions::default(), on_change)
+    }
+
+    /// Same as [`Resources::watch`], but delivers the new content through a channel.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use appres::{Resources, Value};
+    ///
+    /// let resources = Resources::new_app_relative_to_config("projectile").unwrap();
+    ///
+    /// // Wait for the user to edit config.yaml.
+    /// let (watcher, changes) = resources.watch_channel::<Value>("config.yaml").unwrap();
+    /// let config = changes.recv().unwrap();
+    /// ```
+    pub fn watch_channel<T>(&self, path: impl AsRef<Path>) -> Result<(Watcher, Receiver<T>)>
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let (sender, receiver) = channel();
+        let watcher = self.watch(path, move |thing| {
+            let _ = sender.send(thing);
+        })?;
+        Ok((watcher, receiver))
+    }
+
+    /// Same as [`Resources::watch`], with control over polling and debouncing.
+    pub fn watch_with<T, F>(
+        &self,
+        path: impl AsRef<Path>,
+        options: WatchOptions,
+        mut on_change: F,
+    ) -> Result<Watcher>
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnMut(T) + Send + 'static,
+    {
+        let relative_path = path.as_ref().to_path_buf();
+        // Fail early rather than in the background for files that cannot be parsed.
+        self.format_for(&relative_path)?;
+
+        // Fail early for paths outside of the resources directory as well.
+        self.backend_path(&relative_path)?;
+
+        let resources = self.clone();
+        let (stop, stopped) = channel();
+
+        let thread = thread::spawn(move || {
+            let mut state = WatchState::new(resources.clone(), relative_path.clone());
+            loop {
+                match stopped.recv_timeout(options.poll_interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => return,
+                }
+
+                if let Some(content) = state.poll(options.debounce) {
+                    if let Ok(thing) = resources.deserialize_for(&relative_path, &content) {
+                        state.delivered = Some(content);
+                        on_change(thing);
+                    }
+                }
+            }
+        });
+
+        Ok(Watcher {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+struct WatchState {
+    resources: Resources,
+    path: PathBuf,
+    seen: Option<Fingerprint>,
+    changed_at: Option<Instant>,
+    delivered: Option<Vec<u8>>,
+}
+
+impl WatchState {
+    fn new(resources: Resources, path: PathBuf) -> Self {
+        Self {
+            seen: Fingerprint::of(&resources, &path),
+            changed_at: None,
+            delivered: resources.load_bytes(&path).ok(),
+            resources,
+            path,
+        }
+    }
+
+    /// Returns the new content of the file once it has changed and settled down.
+    fn poll(&mut self, debounce: Duration) -> Option<Vec<u8>> {
+        let fingerprint = Fingerprint::of(&self.resources, &self.path);
+        if fingerprint != self.seen {
+            self.seen = fingerprint;
+            self.changed_at = Some(Instant::now());
+            return None;
+        }
+
+        if self.changed_at?.elapsed() < debounce {
+            return None;
+        }
+        self.changed_at = None;
+
+        // A missing file is most likely being replaced, wait for the new one.
+        let content = self.resources.load_bytes(&self.path).ok()?;
+        if self.delivered.as_ref() == Some(&content) {
+            return None;
+        }
+        Some(content)
+    }
+}